@@ -0,0 +1,25 @@
+use serde::Deserialize;
+
+use crate::record::{deserialize_to_i64, deserialize_to_option_i64};
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DnssecRecord {
+    #[serde(deserialize_with = "deserialize_to_i64")]
+    pub key_tag: i64,
+    #[serde(deserialize_with = "deserialize_to_i64")]
+    pub alg: i64,
+    #[serde(deserialize_with = "deserialize_to_i64")]
+    pub digest_type: i64,
+    pub digest: String,
+    #[serde(default, deserialize_with = "deserialize_to_option_i64")]
+    pub max_sig_life: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_to_option_i64")]
+    pub key_data_flags: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_to_option_i64")]
+    pub key_data_protocol: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_to_option_i64")]
+    pub key_data_algo: Option<i64>,
+    #[serde(default)]
+    pub key_data_pub_key: Option<String>,
+}
@@ -0,0 +1,35 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GlueRecord {
+    pub host: String,
+    pub ips: Vec<IpAddr>,
+}
+
+impl<'de> Deserialize<'de> for GlueRecord {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Ips {
+            #[serde(default)]
+            v4: Vec<Ipv4Addr>,
+            #[serde(default)]
+            v6: Vec<Ipv6Addr>,
+        }
+
+        // Porkbun returns each host as a `[host, {"v4": [...], "v6": [...]}]` pair.
+        let (host, ips) = <(String, Ips)>::deserialize(deserializer)?;
+        let ips = ips
+            .v4
+            .into_iter()
+            .map(IpAddr::from)
+            .chain(ips.v6.into_iter().map(IpAddr::from))
+            .collect();
+
+        Ok(GlueRecord { host, ips })
+    }
+}
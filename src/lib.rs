@@ -1,14 +1,43 @@
+pub mod dnssec;
+pub mod glue;
+mod rate_limit;
 pub mod record;
+pub mod transport;
+pub mod ttl;
 
-use std::net::IpAddr;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
 
-use addr::domain;
+use addr::{dns, domain};
 use serde::Deserialize;
 use serde_json::json;
 use thiserror::Error as ThisError;
 use url::Url;
 
-use record::{Content, Record, Type};
+use dnssec::DnssecRecord;
+use glue::GlueRecord;
+use rate_limit::RateLimiter;
+use record::{Content, Record, RecordDiff, Type};
+use transport::{ReqwestTransport, Transport};
+
+/// The lowest TTL, in seconds, that Porkbun accepts for a record.
+pub const MIN_TTL: i64 = 600;
+
+const DEFAULT_BASE_URL: &str = "https://api.porkbun.com/";
+const IPV4_BASE_URL: &str = "https://api-ipv4.porkbun.com/";
+const DEFAULT_API_VERSION: &str = "v3";
+
+/// How long to wait before retrying a request that failed authentication transiently.
+const AUTH_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 #[derive(ThisError, Debug)]
 pub enum DomainError {
@@ -16,66 +45,468 @@ pub enum DomainError {
     HasPrefix(String),
     #[error("domain {0:?} doesn't have a root")]
     MissingRoot(String),
+    #[error("domain {0:?} is a public suffix, not a registrable domain")]
+    PublicSuffixOnly(String),
+    #[error("domain {0:?} can't be converted to ASCII")]
+    InvalidIdn(String),
 }
 
 #[derive(ThisError, Debug)]
 pub enum ApiError {
     #[error(transparent)]
     Domain(#[from] DomainError),
+    #[error("record {0} not found")]
+    MissingRecord(i64),
+    #[error("multiple records returned for id {0}")]
+    DuplicateRecord(i64),
+    #[error("CNAME records aren't allowed at the apex of {0:?}, use ALIAS instead")]
+    CnameAtApex(String),
+    #[error("TTL {0} is below the minimum of {MIN_TTL}")]
+    InvalidTtl(i64),
+    #[error("multiple {1} records for domain {0:?}")]
+    MultipleRecords(String, &'static str),
+    #[error("TXT value {0:?} is not an SPF record")]
+    NotSpf(String),
+    #[error("extra request fields must be a JSON object, not {0}")]
+    InvalidExtra(serde_json::Value),
+    /// An error Porkbun reported in its response. `status` is the HTTP status it came with, if
+    /// that was an error status.
+    #[error("Porkbun returned an error: {message}")]
+    Api {
+        status: Option<reqwest::StatusCode>,
+        message: String,
+    },
+    #[error("Porkbun rejected the TTL: {message}")]
+    TtlRejected {
+        status: Option<reqwest::StatusCode>,
+        message: String,
+    },
+    #[error("Porkbun doesn't allow this record type here: {message}")]
+    TypeNotAllowed {
+        status: Option<reqwest::StatusCode>,
+        message: String,
+    },
+    #[error("Porkbun's record limit was reached: {message}")]
+    RecordLimit {
+        status: Option<reqwest::StatusCode>,
+        message: String,
+    },
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
     #[error(transparent)]
     UrlParse(#[from] url::ParseError),
 }
 
+impl ApiError {
+    /// Turns a Porkbun error message into the most specific variant it matches.
+    ///
+    /// This is best-effort: Porkbun's messages are meant for humans and may change, so anything
+    /// unrecognized ends up as [`ApiError::Api`].
+    pub(crate) fn from_message(message: &str, status: Option<reqwest::StatusCode>) -> Self {
+        let lower = message.to_lowercase();
+        // Matching whole words keeps e.g. "throttled" from reading as a TTL.
+        let words = lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+        let has_word = |word: &str| words.contains(&word);
+        let message = message.to_string();
+        if has_word("ttl") {
+            ApiError::TtlRejected { status, message }
+        } else if has_word("type")
+            && (lower.contains("not allowed") || lower.contains("not supported"))
+        {
+            ApiError::TypeNotAllowed { status, message }
+        } else if words.windows(2).any(|pair| {
+            matches!(
+                pair,
+                ["record" | "records", "limit" | "limits"]
+                    | ["limit" | "limits", "record" | "records"]
+            )
+        }) || lower.contains("maximum number of record")
+        {
+            ApiError::RecordLimit { status, message }
+        } else {
+            ApiError::Api { status, message }
+        }
+    }
+
+    /// The status and message of an error Porkbun returned, whichever variant it was classified
+    /// as.
+    fn api_error(&self) -> Option<(Option<reqwest::StatusCode>, &str)> {
+        match self {
+            ApiError::Api { status, message }
+            | ApiError::TtlRejected { status, message }
+            | ApiError::TypeNotAllowed { status, message }
+            | ApiError::RecordLimit { status, message } => Some((*status, message)),
+            _ => None,
+        }
+    }
+
+    fn api_message(&self) -> Option<&str> {
+        self.api_error().map(|(_, message)| message)
+    }
+
+    /// Whether the request may succeed if retried later: network failures, timeouts, responses
+    /// with a 429 or 5xx status (with or without an error message from Porkbun), and
+    /// authentication failures that tend to clear up on their own. Everything else, like invalid
+    /// credentials or a rejected record, needs fixing before retrying.
+    pub fn is_transient(&self) -> bool {
+        let retryable = |status: reqwest::StatusCode| {
+            status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        };
+
+        match self {
+            ApiError::Reqwest(err) => {
+                err.is_timeout() || err.is_connect() || err.status().is_some_and(retryable)
+            }
+            _ => {
+                self.api_error()
+                    .is_some_and(|(status, _)| status.is_some_and(retryable))
+                    || self.is_transient_auth()
+            }
+        }
+    }
+
+    /// Whether Porkbun rejected the credentials in a way that may resolve itself, as happens for a
+    /// while after API access is enabled for a key. Plainly invalid keys don't count.
+    fn is_transient_auth(&self) -> bool {
+        self.api_message().is_some_and(|message| {
+            let message = message.to_lowercase();
+            // A domain that isn't opted in to API access stays that way until someone changes it.
+            (message.contains("authentication") || message.contains("api access"))
+                && !message.contains("invalid")
+                && !message.contains("not opted in")
+        })
+    }
+
+    /// Whether Porkbun rejected the request because no record matched it. Other things that can
+    /// be "not found", like the domain, don't count.
+    fn is_no_record(&self) -> bool {
+        self.api_message().is_some_and(|message| {
+            let message = message.to_lowercase();
+            message.contains("no record")
+                || message.contains("record not found")
+                || message.contains("records not found")
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnsureOutcome {
+    Created(i64),
+    Updated(i64),
+    Unchanged(i64),
+}
+
 #[derive(ThisError, Debug)]
 pub enum ClientBuilderError {
     #[error("missing field: {0}")]
     MissingField(String),
+    #[error("environment variable {0} is not set")]
+    MissingEnv(String),
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
     #[error(transparent)]
     UrlParse(#[from] url::ParseError),
+    #[error("failed to verify credentials: {0}")]
+    Auth(#[source] ApiError),
+    #[error("rate limit must be a positive number of requests per second, not {0}")]
+    InvalidRateLimit(f64),
 }
 
+const REDACTED: &str = "***";
+
+/// A key that is left out of `Debug` output, so that logging a client or builder doesn't leak it.
+#[derive(Clone)]
+struct Secret(String);
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// A Porkbun API key together with its secret key.
+///
+/// The `Debug` output leaves out both keys.
+#[derive(Clone)]
+pub struct Credentials {
+    pub apikey: String,
+    pub secretapikey: String,
+}
+
+impl Credentials {
+    /// Reads the keys from `PORKBUN_API_KEY` and `PORKBUN_SECRET_API_KEY`.
+    pub fn from_env() -> Result<Self, ClientBuilderError> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| ClientBuilderError::MissingEnv(name.to_string()))
+        };
+
+        Ok(Self {
+            apikey: var("PORKBUN_API_KEY")?,
+            secretapikey: var("PORKBUN_SECRET_API_KEY")?,
+        })
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("apikey", &REDACTED)
+            .field("secretapikey", &REDACTED)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
 pub struct ClientBuilder {
-    endpoint: Option<Url>,
-    apikey: Option<String>,
-    secretapikey: Option<String>,
+    endpoint: Option<Result<Url, url::ParseError>>,
+    base_url: Option<BaseUrl>,
+    api_version: Option<String>,
+    apikey: Option<Secret>,
+    secretapikey: Option<Secret>,
+    pool_idle_timeout: Option<Option<Duration>>,
+    pool_max_idle_per_host: Option<usize>,
+    proxy: Option<Url>,
+    local_address: Option<IpAddr>,
+    rate_limit: Option<f64>,
+    retry_budget: Option<u32>,
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    danger_accept_invalid_certs: bool,
+    reqwest_client: Option<reqwest::blocking::Client>,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+/// The host set with [`ClientBuilder::base_url`] or [`ClientBuilder::use_ipv4_endpoint`].
+#[derive(Debug, Clone)]
+enum BaseUrl {
+    Ipv4,
+    Custom(Url),
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // A proxy URL can hold a password for basic auth.
+        let proxy = self.proxy.as_ref().map(|proxy| {
+            let mut proxy = proxy.clone();
+            if proxy.password().is_some() {
+                let _ = proxy.set_password(Some(REDACTED));
+            }
+            proxy
+        });
+
+        f.debug_struct("ClientBuilder")
+            .field("endpoint", &self.endpoint)
+            .field("base_url", &self.base_url)
+            .field("api_version", &self.api_version)
+            .field("apikey", &self.apikey)
+            .field("secretapikey", &self.secretapikey)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("proxy", &proxy)
+            .field("local_address", &self.local_address)
+            .field("rate_limit", &self.rate_limit)
+            .field("retry_budget", &self.retry_budget)
+            .finish_non_exhaustive()
+    }
 }
 
 impl ClientBuilder {
     pub fn new() -> Self {
         Self {
             endpoint: None,
+            base_url: None,
+            api_version: None,
             apikey: None,
             secretapikey: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            proxy: None,
+            local_address: None,
+            rate_limit: None,
+            retry_budget: None,
+            #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+            danger_accept_invalid_certs: false,
+            reqwest_client: None,
+            transport: None,
         }
     }
 
     pub fn endpoint(mut self, endpoint: &Url) -> Self {
-        self.endpoint = Some(endpoint.clone());
+        self.endpoint = Some(Ok(endpoint.clone()));
         self
     }
 
     pub fn endpoint_if_some(mut self, endpoint: Option<&Url>) -> Self {
         if let Some(endpoint) = endpoint {
-            self.endpoint = Some(endpoint.clone());
+            self.endpoint = Some(Ok(endpoint.clone()));
         }
         self
     }
+
+    /// Parses `endpoint` as a URL, deferring any parse error to [`ClientBuilder::build`].
+    pub fn endpoint_str(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.parse());
+        self
+    }
+
+    /// Sets the host the endpoint is composed from, e.g. `https://api.porkbun.com/`.
+    ///
+    /// Ignored if a full endpoint is set.
+    pub fn base_url(mut self, base_url: &Url) -> Self {
+        self.base_url = Some(BaseUrl::Custom(base_url.clone()));
+        self
+    }
+
+    /// Uses Porkbun's IPv4-only host, so requests can't go out over a broken IPv6 route.
+    ///
+    /// Ignored if a full endpoint is set.
+    pub fn use_ipv4_endpoint(mut self) -> Self {
+        self.base_url = Some(BaseUrl::Ipv4);
+        self
+    }
+
+    /// Sets the API version the endpoint is composed from, e.g. `v3`.
+    ///
+    /// Ignored if a full endpoint is set.
+    pub fn api_version(mut self, api_version: &str) -> Self {
+        self.api_version = Some(api_version.to_string());
+        self
+    }
+
     pub fn apikey(mut self, apikey: &str) -> Self {
-        self.apikey = Some(apikey.to_string());
+        self.apikey = Some(Secret(apikey.to_string()));
         self
     }
 
     pub fn secretapikey(mut self, secretapikey: &str) -> Self {
-        self.secretapikey = Some(secretapikey.to_string());
+        self.secretapikey = Some(Secret(secretapikey.to_string()));
+        self
+    }
+
+    /// Sets both keys at once, replacing any set with [`Self::apikey`] or [`Self::secretapikey`].
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.apikey = Some(Secret(credentials.apikey));
+        self.secretapikey = Some(Secret(credentials.secretapikey));
+        self
+    }
+
+    /// Sets how long idle connections are kept alive for reuse, or forever if `None`.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sends all requests through `proxy`. Credentials in the URL are used for basic auth.
+    ///
+    /// Without this, the `HTTP_PROXY`/`HTTPS_PROXY` environment variables are respected.
+    pub fn proxy(mut self, proxy: &Url) -> Self {
+        self.proxy = Some(proxy.clone());
+        self
+    }
+
+    /// Sends requests from `address`, e.g. to pick the interface Porkbun is reached through on a
+    /// host with several.
+    pub fn local_address(mut self, address: IpAddr) -> Self {
+        self.local_address = Some(address);
+        self
+    }
+
+    /// Paces requests to at most `per_second` on average, sleeping before a request when needed.
+    /// The limit is shared between clones of the built client.
+    ///
+    /// [`ClientBuilder::build`] fails if `per_second` isn't a finite, positive number.
+    pub fn rate_limit(mut self, per_second: f64) -> Self {
+        self.rate_limit = Some(per_second);
         self
     }
 
+    /// Allows at most `retries` retries in total over the lifetime of the built client, after
+    /// which failures that would be retried are returned straight away. The budget is shared
+    /// between clones of the built client.
+    pub fn retry_budget(mut self, retries: u32) -> Self {
+        self.retry_budget = Some(retries);
+        self
+    }
+
+    /// Disables TLS certificate verification, so that e.g. a local proxy or mock server with a
+    /// self-signed certificate can be used as the endpoint.
+    ///
+    /// This is only meant for testing: it makes every request open to interception.
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.danger_accept_invalid_certs = accept;
+        self
+    }
+
+    /// Sends requests with `client` instead of building a new reqwest client, e.g. to share one
+    /// connection pool with the rest of an application. The other HTTP settings of this builder,
+    /// like the proxy and pool options, are then ignored.
+    pub fn reqwest_client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.reqwest_client = Some(client);
+        self
+    }
+
+    /// Sends requests through `transport` instead of the default reqwest-based one. The HTTP
+    /// settings of this builder, like the proxy and pool options, are then ignored, and
+    /// [`Client::last_response_meta`] stays `None`.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Builds the client and checks the credentials with [`Client::test_auth`], so that bad keys
+    /// are caught at startup rather than on the first real request.
+    pub fn build_and_verify(self) -> Result<Client, ClientBuilderError> {
+        let client = self.build()?;
+        client.test_auth().map_err(ClientBuilderError::Auth)?;
+
+        Ok(client)
+    }
+
     pub fn build(self) -> Result<Client, ClientBuilderError> {
+        let last_response_meta = Arc::new(Mutex::new(None));
+        let transport = match &self.transport {
+            Some(transport) => Arc::clone(transport),
+            None => Arc::new(ReqwestTransport {
+                client: match &self.reqwest_client {
+                    Some(client) => client.clone(),
+                    None => self.build_reqwest()?,
+                },
+                last_response_meta: Arc::clone(&last_response_meta),
+            }),
+        };
+
         let endpoint = match self.endpoint {
-            Some(endpoint) => endpoint,
-            None => "https://api.porkbun.com/api/json/v3/".parse()?,
+            Some(endpoint) => endpoint?,
+            None => {
+                let mut endpoint = match self.base_url {
+                    Some(BaseUrl::Custom(base_url)) => base_url,
+                    Some(BaseUrl::Ipv4) => IPV4_BASE_URL.parse()?,
+                    None => DEFAULT_BASE_URL.parse()?,
+                };
+                let api_version = self.api_version.as_deref().unwrap_or(DEFAULT_API_VERSION);
+                endpoint
+                    .path_segments_mut()
+                    .map_err(|()| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+                    .pop_if_empty()
+                    .extend(["api", "json", api_version, ""]);
+                endpoint
+            }
         };
         let apikey = self
             .apikey
@@ -83,21 +514,99 @@ impl ClientBuilder {
         let secretapikey = self
             .secretapikey
             .ok_or_else(|| ClientBuilderError::MissingField("secretapikey".to_string()))?;
+        if let Some(per_second) = self.rate_limit {
+            if !per_second.is_finite() || per_second <= 0.0 {
+                return Err(ClientBuilderError::InvalidRateLimit(per_second));
+            }
+        }
 
         Ok(Client {
             endpoint,
             apikey,
             secretapikey,
-            client: reqwest::blocking::Client::new(),
+            transport,
+            last_response_meta,
+            rate_limiter: self
+                .rate_limit
+                .map(|per_second| Arc::new(RateLimiter::new(per_second))),
+            retry_budget: self
+                .retry_budget
+                .map(|retries| Arc::new(AtomicU32::new(retries))),
         })
     }
+
+    fn build_reqwest(&self) -> Result<reqwest::blocking::Client, ClientBuilderError> {
+        let mut client = reqwest::blocking::Client::builder();
+        if let Some(timeout) = self.pool_idle_timeout {
+            client = client.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            client = client.pool_max_idle_per_host(max);
+        }
+        if let Some(proxy) = &self.proxy {
+            client = client.proxy(reqwest::Proxy::all(proxy.clone())?);
+        }
+        if let Some(address) = self.local_address {
+            client = client.local_address(address);
+        }
+        #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+        if self.danger_accept_invalid_certs {
+            client = client.danger_accept_invalid_certs(true);
+        }
+
+        Ok(client.build()?)
+    }
+}
+
+/// Metadata from the most recent HTTP response, for throttling introspection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseMeta {
+    pub status: reqwest::StatusCode,
+    pub retry_after: Option<Duration>,
+    pub rate_limit: Vec<(String, String)>,
 }
 
+impl ResponseMeta {
+    fn from_response(resp: &reqwest::blocking::Response) -> Self {
+        let headers = resp.headers();
+        let retry_after = headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        let rate_limit = headers
+            .iter()
+            .filter(|(name, _)| name.as_str().starts_with("x-ratelimit-"))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+
+        Self {
+            status: resp.status(),
+            retry_after,
+            rate_limit,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Client {
     endpoint: Url,
-    apikey: String,
-    secretapikey: String,
-    client: reqwest::blocking::Client,
+    apikey: Secret,
+    secretapikey: Secret,
+    transport: Arc<dyn Transport>,
+    last_response_meta: Arc<Mutex<Option<ResponseMeta>>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry_budget: Option<Arc<AtomicU32>>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("endpoint", &self.endpoint)
+            .field("apikey", &self.apikey)
+            .field("secretapikey", &self.secretapikey)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Client {
@@ -105,20 +614,103 @@ impl Client {
         ClientBuilder::new()
     }
 
+    /// Returns the metadata of the last response received, shared between clones of this client.
+    pub fn last_response_meta(&self) -> Option<ResponseMeta> {
+        self.last_response_meta
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Returns the registrable root of `domain` as a name of its own, e.g. `example.com` for
+    /// `www.example.com`, for passing to the methods that only accept a root domain.
+    pub fn apex_of<'a>(
+        &self,
+        domain: &'a impl RecordName,
+    ) -> Result<domain::Name<'a>, DomainError> {
+        let root = domain.root().ok_or_else(|| missing_root(domain))?;
+
+        addr::parse_domain_name(root).map_err(|_| DomainError::MissingRoot(domain.to_string()))
+    }
+
+    // Pushing segments rather than using `Url::join` keeps the endpoint's last segment even
+    // without a trailing slash, and percent-encodes anything that would otherwise be read as a
+    // query, fragment or scheme.
+    fn url(&self, segments: &[&str]) -> Result<Url, ApiError> {
+        let mut url = self.endpoint.clone();
+        url.path_segments_mut()
+            .map_err(|()| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .extend(segments);
+
+        Ok(url)
+    }
+
+    fn base_payload(&self) -> serde_json::Value {
+        json!({
+            "secretapikey": self.secretapikey.0,
+            "apikey": self.apikey.0,
+        })
+    }
+
+    // Porkbun reports failures as `{"status": "ERROR", "message": ...}`, sometimes with HTTP 200,
+    // so transports hand such bodies back whatever the HTTP status and the message is surfaced here.
+    fn post(&self, url: Url, payload: &serde_json::Value) -> Result<serde_json::Value, ApiError> {
+        match self.post_once(&url, payload) {
+            Err(err) if err.is_transient_auth() && self.take_retry() => {
+                log::debug!("retrying after transient authentication failure: {err}");
+                thread::sleep(AUTH_RETRY_DELAY);
+                self.post_once(&url, payload)
+            }
+            result => result,
+        }
+    }
+
+    /// Uses up one retry from the retry budget, returning whether there was one left.
+    fn take_retry(&self) -> bool {
+        self.retry_budget.as_ref().is_none_or(|budget| {
+            budget
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |retries| {
+                    retries.checked_sub(1)
+                })
+                .is_ok()
+        })
+    }
+
+    fn post_once(
+        &self,
+        url: &Url,
+        payload: &serde_json::Value,
+    ) -> Result<serde_json::Value, ApiError> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire();
+        }
+
+        if log::log_enabled!(log::Level::Debug) {
+            let mut payload = payload.clone();
+            for key in ["apikey", "secretapikey"] {
+                if payload.get(key).is_some() {
+                    payload[key] = serde_json::Value::from(REDACTED);
+                }
+            }
+            log::debug!("POST {url} {payload}");
+        }
+
+        let body = self.transport.post_json(url, payload)?;
+        if body["status"] == "ERROR" {
+            let message = body["message"].as_str().unwrap_or("unknown error");
+            return Err(ApiError::from_message(message, None));
+        }
+
+        Ok(body)
+    }
+
     pub fn test_auth(&self) -> Result<IpAddr, ApiError> {
-        let url = self.endpoint.join("ping")?;
+        let url = self.url(&["ping"])?;
 
-        let payload = json!({
-            "secretapikey": self.secretapikey.as_str(),
-            "apikey": self.apikey.as_str(),
-        });
+        let payload = self.base_payload();
 
-        let resp = self
-            .client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        let resp = self.post(url, &payload)?;
 
         #[derive(Deserialize)]
         #[serde(rename_all = "camelCase")]
@@ -126,41 +718,42 @@ impl Client {
             your_ip: IpAddr,
         }
 
-        Ok(resp.json::<Response>()?.your_ip)
+        Ok(serde_json::from_value::<Response>(resp)?.your_ip)
     }
 
     pub fn create_dns(
         &self,
-        domain: &domain::Name,
+        domain: &impl RecordName,
         content: &Content,
         ttl: Option<i64>,
         prio: Option<i64>,
     ) -> Result<i64, ApiError> {
         let (prefix, root) = split_domain(domain)?;
-        let url = self.endpoint.join("dns/create/")?.join(root)?;
 
-        let mut payload = json!({
-            "secretapikey": self.secretapikey,
-            "apikey": self.apikey,
-            "type": content.type_as_str(),
-            "content": content.value_to_string(),
-        });
-        if let Some(prefix) = prefix {
-            payload["name"] = serde_json::Value::from(prefix);
-        }
-        if let Some(ttl) = ttl {
-            payload["ttl"] = serde_json::Value::from(ttl);
-        }
-        if let Some(prio) = prio {
-            payload["prio"] = serde_json::Value::from(prio);
+        self.create_dns_in_root(&root, prefix.as_deref(), content, ttl, prio)
+    }
+
+    fn create_dns_in_root(
+        &self,
+        root: &str,
+        name: Option<&str>,
+        content: &Content,
+        ttl: Option<i64>,
+        prio: Option<i64>,
+    ) -> Result<i64, ApiError> {
+        check_ttl(ttl)?;
+        check_apex_cname(root, name, content)?;
+        let url = self.url(&["dns", "create", root])?;
+
+        let mut payload = self.base_payload();
+        payload["type"] = serde_json::Value::from(content.type_as_str());
+        payload["content"] = serde_json::Value::from(content.value_to_string());
+        if let Some(name) = name {
+            payload["name"] = serde_json::Value::from(name);
         }
+        apply_optional(&mut payload, ttl, prio);
 
-        let resp = self
-            .client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        let resp = self.post(url, &payload)?;
 
         #[derive(Deserialize)]
         struct Response {
@@ -168,141 +761,333 @@ impl Client {
             id: i64,
         }
 
-        Ok(resp.json::<Response>()?.id)
+        Ok(serde_json::from_value::<Response>(resp)?.id)
+    }
+
+    /// Creates each of `records` under the root domain `domain`, e.g. when importing a zone.
+    ///
+    /// Each entry is a name relative to `domain` (empty or `@` for the apex), the content, the TTL
+    /// and the priority. A failed record doesn't stop the import; the returned list holds the
+    /// outcome of each record in order, with the new ID on success.
+    pub fn import_records(
+        &self,
+        domain: &impl RecordName,
+        records: &[(String, Content, Option<i64>, Option<i64>)],
+    ) -> Result<Vec<Result<i64, ApiError>>, ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        Ok(records
+            .iter()
+            .map(|(name, content, ttl, prio)| {
+                let name = Some(name.as_str()).filter(|name| !name.is_empty() && *name != "@");
+                self.create_dns_in_root(&root, name, content, *ttl, *prio)
+            })
+            .collect())
+    }
+
+    pub fn create_dns_record(
+        &self,
+        domain: &impl RecordName,
+        content: &Content,
+        ttl: Option<i64>,
+        prio: Option<i64>,
+    ) -> Result<Record, ApiError> {
+        let (_, root) = split_domain(domain)?;
+        let id = self.create_dns(domain, content, ttl, prio)?;
+
+        self.retrieve_dns_in_root(&root, Some(id))?
+            .into_iter()
+            .next()
+            .ok_or(ApiError::MissingRecord(id))
     }
 
     pub fn edit_dns(
         &self,
-        domain: &domain::Name,
+        domain: &impl RecordName,
         id: i64,
         content: &Content,
         ttl: Option<i64>,
         prio: Option<i64>,
     ) -> Result<(), ApiError> {
-        let (prefix, root) = split_domain(domain)?;
-        let url = self
-            .endpoint
-            .join("dns/edit/")?
-            .join(&format!("{root}/"))?
-            .join(&id.to_string())?;
-
-        let mut payload = json!({
-            "secretapikey": self.secretapikey,
-            "apikey": self.apikey,
-            "type": content.type_as_str(),
-            "content": content.value_to_string(),
-        });
-        if let Some(prefix) = prefix {
-            payload["name"] = serde_json::Value::from(prefix);
-        }
-        if let Some(ttl) = ttl {
-            payload["ttl"] = serde_json::Value::from(ttl);
-        }
-        if let Some(prio) = prio {
-            payload["prio"] = serde_json::Value::from(prio);
+        let (prefix, _) = split_domain(domain)?;
+
+        self.edit_dns_full(domain, id, prefix.as_deref(), content, ttl, prio)
+    }
+
+    /// Changes the content of `record`, a record of `domain`, keeping its TTL and priority.
+    pub fn edit_dns_from_record(
+        &self,
+        domain: &impl RecordName,
+        record: &Record,
+        new_content: &Content,
+    ) -> Result<(), ApiError> {
+        self.edit_dns(
+            domain,
+            record.id,
+            new_content,
+            Some(record.ttl),
+            record.prio,
+        )
+    }
+
+    /// Edits record `id` under the root of `domain`, setting its name to `name` (a prefix, or
+    /// the apex if `None`) rather than the prefix of `domain`. This renames a record in place.
+    pub fn edit_dns_full(
+        &self,
+        domain: &impl RecordName,
+        id: i64,
+        name: Option<&str>,
+        content: &Content,
+        ttl: Option<i64>,
+        prio: Option<i64>,
+    ) -> Result<(), ApiError> {
+        let (_, root) = split_domain(domain)?;
+        check_ttl(ttl)?;
+        check_apex_cname(&root, name, content)?;
+        let url = self.url(&["dns", "edit", &root, &id.to_string()])?;
+
+        let mut payload = self.base_payload();
+        payload["type"] = serde_json::Value::from(content.type_as_str());
+        payload["content"] = serde_json::Value::from(content.value_to_string());
+        if let Some(name) = name {
+            payload["name"] = serde_json::Value::from(name);
         }
+        apply_optional(&mut payload, ttl, prio);
 
-        self.client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        self.post(url, &payload)?;
 
         Ok(())
     }
 
     pub fn edit_dns_by_name_type(
         &self,
-        domain: &domain::Name,
+        domain: &impl RecordName,
         content: &Content,
         ttl: Option<i64>,
         prio: Option<i64>,
     ) -> Result<(), ApiError> {
         let (prefix, root) = split_domain(domain)?;
-        let url = self
-            .endpoint
-            .join("dns/editByNameType/")?
-            .join(&format!("{root}/"))?
-            .join(&format!("{}/", content.type_as_str()))?
-            .join(prefix.unwrap_or(""))?;
-
-        let mut payload = json!({
-            "secretapikey": self.secretapikey,
-            "apikey": self.apikey,
-            "content": content.value_to_string(),
-        });
-        if let Some(ttl) = ttl {
-            payload["ttl"] = serde_json::Value::from(ttl);
+        check_ttl(ttl)?;
+        check_apex_cname(&root, prefix.as_deref(), content)?;
+        let mut segments = vec!["dns", "editByNameType", &root, content.type_as_str()];
+        segments.extend(prefix.as_deref());
+        let url = self.url(&segments)?;
+
+        let mut payload = self.base_payload();
+        payload["content"] = serde_json::Value::from(content.value_to_string());
+        apply_optional(&mut payload, ttl, prio);
+
+        self.post(url, &payload)?;
+
+        Ok(())
+    }
+
+    /// Like [`Client::edit_dns_by_name_type`], but creates the record when Porkbun reports that
+    /// no record of that name and type exists. Returns the new record's ID if one was created.
+    pub fn upsert_dns_by_name_type(
+        &self,
+        domain: &impl RecordName,
+        content: &Content,
+        ttl: Option<i64>,
+        prio: Option<i64>,
+    ) -> Result<Option<i64>, ApiError> {
+        match self.edit_dns_by_name_type(domain, content, ttl, prio) {
+            Ok(()) => Ok(None),
+            Err(err) if err.is_no_record() => {
+                Ok(Some(self.create_dns(domain, content, ttl, prio)?))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Makes sure `domain` has exactly one record of the content's type, with the given content,
+    /// creating or editing it as needed. `ttl` and `prio` are only compared when they are `Some`.
+    pub fn ensure_dns(
+        &self,
+        domain: &impl RecordName,
+        content: &Content,
+        ttl: Option<i64>,
+        prio: Option<i64>,
+    ) -> Result<EnsureOutcome, ApiError> {
+        // Porkbun stores TTLs below the minimum as the minimum, so such a TTL would never compare
+        // equal and every call would edit the record again.
+        check_ttl(ttl)?;
+        let records = self.retrieve_dns_by_name_type(domain, &Type::from(content))?;
+
+        match records.as_slice() {
+            [] => Ok(EnsureOutcome::Created(
+                self.create_dns(domain, content, ttl, prio)?,
+            )),
+            [record] => {
+                if record.matches(content, ttl, prio) {
+                    Ok(EnsureOutcome::Unchanged(record.id))
+                } else {
+                    self.edit_dns(domain, record.id, content, ttl, prio)?;
+                    Ok(EnsureOutcome::Updated(record.id))
+                }
+            }
+            _ => Err(ApiError::MultipleRecords(
+                domain.to_string(),
+                content.type_as_str(),
+            )),
+        }
+    }
+
+    /// Makes sure `domain` has exactly one SPF record, with the given content, creating or editing
+    /// it as needed. Other TXT records at the same name are left alone.
+    ///
+    /// A name with more than one SPF record is invalid, so this refuses to create a second one and
+    /// fails with [`ApiError::MultipleRecords`] if several already exist.
+    pub fn ensure_spf(
+        &self,
+        domain: &impl RecordName,
+        content: &Content,
+        ttl: Option<i64>,
+    ) -> Result<EnsureOutcome, ApiError> {
+        if !content.is_spf() {
+            return Err(ApiError::NotSpf(content.value_to_string()));
         }
-        if let Some(prio) = prio {
-            payload["prio"] = serde_json::Value::from(prio);
+        check_ttl(ttl)?;
+        let mut records = self.retrieve_dns_by_name_type(domain, &Type::Txt)?;
+        records.retain(|record| record.content.is_spf());
+
+        match records.as_slice() {
+            [] => Ok(EnsureOutcome::Created(
+                self.create_dns(domain, content, ttl, None)?,
+            )),
+            [record] => {
+                if record.matches(content, ttl, None) {
+                    Ok(EnsureOutcome::Unchanged(record.id))
+                } else {
+                    self.edit_dns(domain, record.id, content, ttl, None)?;
+                    Ok(EnsureOutcome::Updated(record.id))
+                }
+            }
+            _ => Err(ApiError::MultipleRecords(domain.to_string(), "SPF")),
         }
+    }
 
-        self.client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+    /// Ensures `domain` has an A or AAAA record, depending on the family of `ip`, pointing at it.
+    pub fn set_address_record(
+        &self,
+        domain: &impl RecordName,
+        ip: IpAddr,
+        ttl: Option<i64>,
+    ) -> Result<EnsureOutcome, ApiError> {
+        self.ensure_dns(domain, &Content::from_ip(ip), ttl, None)
+    }
 
-        Ok(())
+    /// Deletes record `id` under the root domain `domain`. A record that is already gone counts
+    /// as deleted, so running a cleanup twice doesn't fail; see [`Client::delete_dns_strict`].
+    pub fn delete_dns(&self, domain: &impl RecordName, id: i64) -> Result<(), ApiError> {
+        match self.delete_dns_strict(domain, id) {
+            Err(err) if err.is_no_record() => Ok(()),
+            result => result,
+        }
     }
 
-    pub fn delete_dns(&self, domain: &domain::Name, id: i64) -> Result<(), ApiError> {
+    /// Like [`Client::delete_dns`], but fails if Porkbun reports that the record doesn't exist.
+    pub fn delete_dns_strict(&self, domain: &impl RecordName, id: i64) -> Result<(), ApiError> {
         let (prefix, root) = split_domain(domain)?;
         if prefix.is_some() {
             return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
         }
 
-        let url = self
-            .endpoint
-            .join("dns/delete/")?
-            .join(&format!("{root}/"))?
-            .join(&id.to_string())?;
+        self.delete_dns_in_root(&root, id)
+    }
 
-        let payload = json!({
-            "secretapikey": self.secretapikey,
-            "apikey": self.apikey,
-        });
+    /// Deletes each of the records `ids` under the root domain `domain`. A failed delete doesn't
+    /// stop the others; the returned list holds the outcome for each ID in order. As with
+    /// [`Client::delete_dns`], a record that is already gone counts as deleted.
+    pub fn delete_dns_bulk(
+        &self,
+        domain: &impl RecordName,
+        ids: &[i64],
+    ) -> Result<Vec<Result<(), ApiError>>, ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
 
-        self.client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        Ok(ids
+            .iter()
+            .map(|id| match self.delete_dns_in_root(&root, *id) {
+                Err(err) if err.is_no_record() => Ok(()),
+                result => result,
+            })
+            .collect())
+    }
+
+    fn delete_dns_in_root(&self, root: &str, id: i64) -> Result<(), ApiError> {
+        let url = self.url(&["dns", "delete", root, &id.to_string()])?;
+
+        let payload = self.base_payload();
+
+        self.post(url, &payload)?;
 
         Ok(())
     }
 
+    /// Deletes the records at `domain` whose content is exactly `content`, leaving other records
+    /// of the same type alone, and returns how many were deleted.
+    pub fn delete_dns_matching(
+        &self,
+        domain: &impl RecordName,
+        content: &Content,
+    ) -> Result<usize, ApiError> {
+        let (_, root) = split_domain(domain)?;
+        let records = self.retrieve_dns_by_name_type(domain, &Type::from(content))?;
+
+        let mut count = 0;
+        for record in records.iter().filter(|record| record.content == *content) {
+            self.delete_dns_in_root(&root, record.id)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
     pub fn delete_dns_by_name_type(
         &self,
-        domain: &domain::Name,
+        domain: &impl RecordName,
         type_: &Type,
     ) -> Result<(), ApiError> {
         let (prefix, root) = split_domain(domain)?;
-        let url = self
-            .endpoint
-            .join("dns/deleteByNameType/")?
-            .join(&format!("{root}/"))?
-            .join(&format!("{}/", type_.as_str()))?
-            .join(prefix.unwrap_or(""))?;
-
-        let payload = json!({
-            "secretapikey": self.secretapikey,
-            "apikey": self.apikey,
-        });
+        let mut segments = vec!["dns", "deleteByNameType", &root, type_.as_str()];
+        segments.extend(prefix.as_deref());
+        let url = self.url(&segments)?;
+
+        let payload = self.base_payload();
 
-        self.client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        self.post(url, &payload)?;
 
         Ok(())
     }
 
+    pub fn delete_dns_by_name_type_counted(
+        &self,
+        domain: &impl RecordName,
+        type_: &Type,
+    ) -> Result<usize, ApiError> {
+        let count = self.retrieve_dns_by_name_type(domain, type_)?.len();
+        if count > 0 {
+            self.delete_dns_by_name_type(domain, type_)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Retrieves the record with ID `id` under the root domain `domain`, or all of its records if
+    /// `id` is `None`.
+    ///
+    /// Porkbun's `dns/retrieve` endpoint isn't paginated: it takes no offset and returns every
+    /// record of the domain in one response, so the result is never a truncated page.
     pub fn retrieve_dns(
         &self,
-        domain: &domain::Name,
+        domain: &impl RecordName,
         id: Option<i64>,
     ) -> Result<Vec<Record>, ApiError> {
         let (prefix, root) = split_domain(domain)?;
@@ -310,75 +1095,489 @@ impl Client {
             return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
         }
 
-        let url = self
-            .endpoint
-            .join("dns/retrieve/")?
-            .join(&format!("{root}/"))?
-            .join(&id.map_or_else(|| "".to_string(), |id| id.to_string()))?;
+        self.retrieve_dns_in_root(&root, id)
+    }
 
-        let payload = json!({
-            "secretapikey": self.secretapikey,
-            "apikey": self.apikey,
-        });
+    /// Retrieves every record under the root domain `domain` and formats them as a BIND zone
+    /// file with [`record::to_zonefile`].
+    pub fn export_zonefile(&self, domain: &impl RecordName) -> Result<String, ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let records = self.retrieve_dns_in_root(&root, None)?;
+
+        Ok(record::to_zonefile(&root, &records))
+    }
+
+    pub fn count_dns(&self, domain: &impl RecordName) -> Result<usize, ApiError> {
+        Ok(self.retrieve_dns(domain, None)?.len())
+    }
+
+    pub fn retrieve_dns_single(
+        &self,
+        domain: &impl RecordName,
+        id: i64,
+    ) -> Result<Option<Record>, ApiError> {
+        let mut records = self.retrieve_dns(domain, Some(id))?;
+        if records.len() > 1 {
+            return Err(ApiError::DuplicateRecord(id));
+        }
+
+        Ok(records.pop())
+    }
+
+    /// Compares the current records of the root domain `domain` with `snapshot`, e.g. a set
+    /// retrieved earlier and saved, to find changes made elsewhere, like in the web interface.
+    pub fn diff_against(
+        &self,
+        domain: &impl RecordName,
+        snapshot: &[Record],
+    ) -> Result<Vec<RecordDiff>, ApiError> {
+        let records = self.retrieve_dns(domain, None)?;
+
+        Ok(record::diff(snapshot, &records))
+    }
+
+    fn retrieve_dns_in_root(&self, root: &str, id: Option<i64>) -> Result<Vec<Record>, ApiError> {
+        let url = self.url(&[
+            "dns",
+            "retrieve",
+            root,
+            &id.map_or_else(|| "".to_string(), |id| id.to_string()),
+        ])?;
+
+        let payload = self.base_payload();
 
-        let resp = self
-            .client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        let resp = self.post(url, &payload)?;
+        warn_unknown_record_fields(&resp);
 
         #[derive(Deserialize)]
         struct Response {
             records: Vec<Record>,
         }
 
-        let resp = resp.json::<Response>()?;
+        let resp = serde_json::from_value::<Response>(resp)?;
 
         Ok(resp.records)
     }
 
+    /// Retrieves every record of type `type_` under the root domain `domain`, whatever its name.
+    pub fn retrieve_dns_by_type(
+        &self,
+        domain: &impl RecordName,
+        type_: &Type,
+    ) -> Result<Vec<Record>, ApiError> {
+        let mut records = self.retrieve_dns(domain, None)?;
+        records.retain(|record| record.record_type() == *type_);
+
+        Ok(records)
+    }
+
+    /// Retrieves every record under the root domain `domain`, grouped by type.
+    pub fn retrieve_dns_grouped(
+        &self,
+        domain: &impl RecordName,
+    ) -> Result<HashMap<Type, Vec<Record>>, ApiError> {
+        let mut grouped = HashMap::<_, Vec<_>>::new();
+        for record in self.retrieve_dns(domain, None)? {
+            grouped
+                .entry(record.record_type())
+                .or_default()
+                .push(record);
+        }
+
+        Ok(grouped)
+    }
+
     pub fn retrieve_dns_by_name_type(
         &self,
-        domain: &domain::Name,
+        domain: &impl RecordName,
         type_: &Type,
     ) -> Result<Vec<Record>, ApiError> {
         let (prefix, root) = split_domain(domain)?;
-        let url = self
-            .endpoint
-            .join("dns/retrieveByNameType/")?
-            .join(&format!("{root}/"))?
-            .join(&format!("{}/", type_.as_str()))?
-            .join(prefix.unwrap_or(""))?;
-
-        let payload = json!({
-            "secretapikey": self.secretapikey,
-            "apikey": self.apikey,
-        });
+        let mut segments = vec!["dns", "retrieveByNameType", &root, type_.as_str()];
+        segments.extend(prefix.as_deref());
+        let url = self.url(&segments)?;
+
+        let payload = self.base_payload();
 
-        let resp = self
-            .client
-            .post(url)
-            .json(&payload)
-            .send()?
-            .error_for_status()?;
+        let resp = self.post(url, &payload)?;
+        warn_unknown_record_fields(&resp);
 
         #[derive(Deserialize)]
         struct Response {
             records: Vec<Record>,
         }
 
-        let resp = resp.json::<Response>()?;
+        let resp = serde_json::from_value::<Response>(resp)?;
 
         Ok(resp.records)
     }
+
+    /// Retrieves the content of the record of type `type_` at `domain`, or `None` if there is no
+    /// such record. Fails with [`ApiError::MultipleRecords`] if there are several.
+    pub fn get_current(
+        &self,
+        domain: &impl RecordName,
+        type_: &Type,
+    ) -> Result<Option<Content>, ApiError> {
+        let mut records = self.retrieve_dns_by_name_type(domain, type_)?;
+
+        match records.len() {
+            0 => Ok(None),
+            1 => Ok(records.pop().map(Record::into_content)),
+            _ => Err(ApiError::MultipleRecords(
+                domain.to_string(),
+                type_.as_str(),
+            )),
+        }
+    }
+
+    pub fn create_dnssec_record(
+        &self,
+        domain: &impl RecordName,
+        record: &DnssecRecord,
+    ) -> Result<(), ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let url = self.url(&["dns", "createDnssecRecord", &root])?;
+
+        let mut payload = self.base_payload();
+        payload["keyTag"] = serde_json::Value::from(record.key_tag.to_string());
+        payload["alg"] = serde_json::Value::from(record.alg.to_string());
+        payload["digestType"] = serde_json::Value::from(record.digest_type.to_string());
+        payload["digest"] = serde_json::Value::from(record.digest.as_str());
+        if let Some(max_sig_life) = record.max_sig_life {
+            payload["maxSigLife"] = serde_json::Value::from(max_sig_life.to_string());
+        }
+        if let Some(key_data_flags) = record.key_data_flags {
+            payload["keyDataFlags"] = serde_json::Value::from(key_data_flags.to_string());
+        }
+        if let Some(key_data_protocol) = record.key_data_protocol {
+            payload["keyDataProtocol"] = serde_json::Value::from(key_data_protocol.to_string());
+        }
+        if let Some(key_data_algo) = record.key_data_algo {
+            payload["keyDataAlgo"] = serde_json::Value::from(key_data_algo.to_string());
+        }
+        if let Some(key_data_pub_key) = &record.key_data_pub_key {
+            payload["keyDataPubKey"] = serde_json::Value::from(key_data_pub_key.as_str());
+        }
+
+        self.post(url, &payload)?;
+
+        Ok(())
+    }
+
+    pub fn get_dnssec_records(
+        &self,
+        domain: &impl RecordName,
+    ) -> Result<Vec<DnssecRecord>, ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let url = self.url(&["dns", "getDnssecRecords", &root])?;
+
+        let payload = self.base_payload();
+
+        let resp = self.post(url, &payload)?;
+
+        // Porkbun keys the records by their key tag, but an empty set comes back as a list.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Records {
+            Map(HashMap<String, DnssecRecord>),
+            List(Vec<DnssecRecord>),
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            records: Records,
+        }
+
+        let mut records = match serde_json::from_value::<Response>(resp)?.records {
+            Records::Map(records) => records.into_values().collect::<Vec<_>>(),
+            Records::List(records) => records,
+        };
+        records.sort_by_key(|record| record.key_tag);
+
+        Ok(records)
+    }
+
+    pub fn delete_dnssec_record(
+        &self,
+        domain: &impl RecordName,
+        key_tag: i64,
+    ) -> Result<(), ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let url = self.url(&["dns", "deleteDnssecRecord", &root, &key_tag.to_string()])?;
+
+        let payload = self.base_payload();
+
+        self.post(url, &payload)?;
+
+        Ok(())
+    }
+
+    pub fn create_glue(
+        &self,
+        domain: &impl RecordName,
+        subdomain: &str,
+        ips: &[IpAddr],
+    ) -> Result<(), ApiError> {
+        self.post_glue("createGlue", domain, subdomain, ips)
+    }
+
+    pub fn update_glue(
+        &self,
+        domain: &impl RecordName,
+        subdomain: &str,
+        ips: &[IpAddr],
+    ) -> Result<(), ApiError> {
+        self.post_glue("updateGlue", domain, subdomain, ips)
+    }
+
+    pub fn delete_glue(&self, domain: &impl RecordName, subdomain: &str) -> Result<(), ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let url = self.url(&["domain", "deleteGlue", &root, subdomain])?;
+
+        let payload = self.base_payload();
+
+        self.post(url, &payload)?;
+
+        Ok(())
+    }
+
+    pub fn get_glue(&self, domain: &impl RecordName) -> Result<Vec<GlueRecord>, ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let url = self.url(&["domain", "getGlue", &root])?;
+
+        let payload = self.base_payload();
+
+        let resp = self.post(url, &payload)?;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(default)]
+            hosts: Vec<GlueRecord>,
+        }
+
+        Ok(serde_json::from_value::<Response>(resp)?.hosts)
+    }
+
+    /// Turns automatic renewal of the root domain `domain` on or off.
+    pub fn set_auto_renew(&self, domain: &impl RecordName, enabled: bool) -> Result<(), ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let url = self.url(&["domain", "updateAutoRenew", &root])?;
+
+        let mut payload = self.base_payload();
+        payload["status"] = serde_json::Value::from(if enabled { "on" } else { "off" });
+
+        let resp = self.post(url, &payload)?;
+
+        // The overall status can be SUCCESS while the domain itself failed.
+        let result = &resp["results"][root.as_ref()];
+        if result["status"] == "ERROR" {
+            let message = result["message"].as_str().unwrap_or("unknown error");
+            return Err(ApiError::from_message(message, None));
+        }
+
+        Ok(())
+    }
+
+    fn post_glue(
+        &self,
+        path: &str,
+        domain: &impl RecordName,
+        subdomain: &str,
+        ips: &[IpAddr],
+    ) -> Result<(), ApiError> {
+        let (prefix, root) = split_domain(domain)?;
+        if prefix.is_some() {
+            return Err(ApiError::Domain(DomainError::HasPrefix(domain.to_string())));
+        }
+
+        let url = self.url(&["domain", path, &root, subdomain])?;
+
+        let mut payload = self.base_payload();
+        payload["ips"] =
+            serde_json::Value::from(ips.iter().map(IpAddr::to_string).collect::<Vec<_>>());
+
+        self.post(url, &payload)?;
+
+        Ok(())
+    }
+
+    /// Posts to `path` relative to the endpoint, with the API keys merged into the `extra`
+    /// object, and returns the raw JSON response. `extra` may also be `null` to send only the
+    /// keys; anything else fails with [`ApiError::InvalidExtra`].
+    pub fn post_raw(
+        &self,
+        path: &str,
+        extra: serde_json::Value,
+    ) -> Result<serde_json::Value, ApiError> {
+        let extra = match extra {
+            serde_json::Value::Object(extra) => extra,
+            serde_json::Value::Null => serde_json::Map::new(),
+            extra => return Err(ApiError::InvalidExtra(extra)),
+        };
+        let url = self.url(&path.trim_matches('/').split('/').collect::<Vec<_>>())?;
+
+        let mut payload = self.base_payload();
+        for (key, value) in extra {
+            payload[key] = value;
+        }
+
+        let resp = self.post(url, &payload)?;
+
+        Ok(resp)
+    }
+}
+
+/// A name records can be managed under.
+///
+/// This is implemented for [`domain::Name`], and for [`dns::Name`] so that names a domain name
+/// can't hold, like the wildcard `*.example.com` or `_dmarc.example.com`, can be used as well.
+pub trait RecordName: std::fmt::Display {
+    /// The registrable root, like `example.com`.
+    fn root(&self) -> Option<&str>;
+    /// The labels before the root, like `www`.
+    fn prefix(&self) -> Option<&str>;
+    /// Whether the whole name is a public suffix, like `co.uk`.
+    fn is_suffix(&self) -> bool;
+}
+
+impl RecordName for domain::Name<'_> {
+    fn root(&self) -> Option<&str> {
+        domain::Name::root(self)
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        domain::Name::prefix(self)
+    }
+
+    fn is_suffix(&self) -> bool {
+        self.suffix() == self.as_str()
+    }
+}
+
+impl RecordName for dns::Name<'_> {
+    // A wildcard directly under a public suffix, like `*.co.uk`, is reported as the root; it
+    // isn't a domain anyone can register, so it has no root at all.
+    fn root(&self) -> Option<&str> {
+        dns::Name::root(self).filter(|root| !root.starts_with('*'))
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        RecordName::root(self)?;
+        dns::Name::prefix(self)
+    }
+
+    fn is_suffix(&self) -> bool {
+        self.suffix() == Some(self.as_str())
+    }
+}
+
+/// Splits `name` into its prefix (if any) and its registrable root, as Porkbun expects them.
+///
+/// For example, `www.example.co.uk` splits into `www` and `example.co.uk`. Internationalized
+/// labels are converted to their ASCII (punycode) form, so `café.com` becomes `xn--caf-dma.com`.
+pub fn split_domain<'a>(
+    name: &'a impl RecordName,
+) -> Result<(Option<Cow<'a, str>>, Cow<'a, str>), DomainError> {
+    let to_ascii = |labels: &'a str| -> Result<Cow<'a, str>, DomainError> {
+        if labels.is_ascii() {
+            Ok(Cow::Borrowed(labels))
+        } else {
+            idna::domain_to_ascii(labels)
+                .map(Cow::Owned)
+                .map_err(|_| DomainError::InvalidIdn(name.to_string()))
+        }
+    };
+
+    let root = name.root().ok_or_else(|| missing_root(name))?;
+    // A fully qualified name keeps its trailing dot in the root, which Porkbun doesn't expect.
+    let root = root.strip_suffix('.').unwrap_or(root);
+    let prefix = name.prefix().map(to_ascii).transpose()?;
+
+    Ok((prefix, to_ascii(root)?))
+}
+
+fn missing_root(name: &impl RecordName) -> DomainError {
+    if name.is_suffix() {
+        DomainError::PublicSuffixOnly(name.to_string())
+    } else {
+        DomainError::MissingRoot(name.to_string())
+    }
 }
 
-fn split_domain<'a>(name: &'a domain::Name) -> Result<(Option<&'a str>, &'a str), DomainError> {
-    let root = name
-        .root()
-        .ok_or_else(|| DomainError::MissingRoot(name.to_string()))?;
-    let prefix = name.prefix();
+/// Returns whether `name` is a registrable root without a prefix, i.e. a name accepted by the
+/// methods that reject prefixed domains.
+pub fn is_apex(name: &impl RecordName) -> bool {
+    name.root().is_some() && name.prefix().is_none()
+}
+
+fn check_ttl(ttl: Option<i64>) -> Result<(), ApiError> {
+    match ttl {
+        Some(ttl) if ttl < MIN_TTL => Err(ApiError::InvalidTtl(ttl)),
+        _ => Ok(()),
+    }
+}
+
+/// Logs a warning for any record field this crate doesn't know about.
+///
+/// Records are deserialized leniently so that a field added by Porkbun doesn't break existing
+/// callers, but a renamed field would otherwise go unnoticed until it showed up as a wrong value.
+fn warn_unknown_record_fields(resp: &serde_json::Value) {
+    let Some(records) = resp["records"].as_array() else {
+        return;
+    };
+
+    for record in records.iter().filter_map(serde_json::Value::as_object) {
+        for key in record.keys() {
+            if !record::RECORD_FIELDS.contains(&key.as_str()) {
+                log::warn!("Porkbun returned unknown record field {key:?}");
+            }
+        }
+    }
+}
 
-    Ok((prefix, root))
+/// Porkbun rejects a CNAME at the apex, where it would clash with the SOA and NS records, so
+/// this fails early with a pointer to ALIAS instead.
+fn check_apex_cname(root: &str, name: Option<&str>, content: &Content) -> Result<(), ApiError> {
+    match (name, content) {
+        (None, Content::Cname(_)) => Err(ApiError::CnameAtApex(root.to_string())),
+        _ => Ok(()),
+    }
+}
+
+fn apply_optional(payload: &mut serde_json::Value, ttl: Option<i64>, prio: Option<i64>) {
+    if let Some(ttl) = ttl {
+        payload["ttl"] = serde_json::Value::from(ttl);
+    }
+    if let Some(prio) = prio {
+        payload["prio"] = serde_json::Value::from(prio);
+    }
 }
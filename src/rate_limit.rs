@@ -0,0 +1,56 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A token bucket allowing `per_second` requests per second on average, with bursts of up to
+/// `per_second` requests (at least one).
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    per_second: f64,
+    capacity: f64,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(per_second: f64) -> Self {
+        let capacity = per_second.max(1.0);
+        Self {
+            per_second,
+            capacity,
+            state: Mutex::new(State {
+                tokens: capacity,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// Blocks until a request may be made. The lock is held while sleeping so that waiters are
+    /// let through one at a time.
+    pub(crate) fn acquire(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.per_second).min(self.capacity);
+        state.last = now;
+
+        if state.tokens < 1.0 {
+            // A tiny rate gives a wait too long for a `Duration`; waiting "forever" is the same.
+            thread::sleep(
+                Duration::try_from_secs_f64((1.0 - state.tokens) / self.per_second)
+                    .unwrap_or(Duration::MAX),
+            );
+            state.tokens = 1.0;
+            state.last = Instant::now();
+        }
+        state.tokens -= 1.0;
+    }
+}
@@ -1,9 +1,38 @@
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
 
-use serde::Deserialize;
-use strum_macros::IntoStaticStr;
+use serde::{ser::SerializeStruct, Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, IntoStaticStr};
+use thiserror::Error as ThisError;
 
-#[derive(Debug, Deserialize, PartialEq, Eq, IntoStaticStr)]
+#[derive(ThisError, Debug)]
+pub enum ContentError {
+    #[error(transparent)]
+    AddrParse(#[from] std::net::AddrParseError),
+    #[error("invalid TLSA record {0:?}: {1}")]
+    InvalidTlsa(String, &'static str),
+    #[error("invalid SVCB record {0:?}: {1}")]
+    InvalidSvcb(String, &'static str),
+    #[error("unsupported record type {0:?}, expected one of {supported}", supported = supported_types())]
+    UnsupportedType(String),
+}
+
+fn supported_types() -> String {
+    Type::all()
+        .map(|type_| type_.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(
+    Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, IntoStaticStr, EnumIter,
+)]
 #[serde(rename_all = "UPPERCASE")]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum Type {
@@ -22,6 +51,11 @@ pub enum Type {
 }
 
 impl Type {
+    /// Every record type this crate supports.
+    pub fn all() -> impl Iterator<Item = Type> {
+        Type::iter()
+    }
+
     pub fn as_str(&self) -> &'static str {
         self.into()
     }
@@ -44,7 +78,10 @@ impl Type {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, IntoStaticStr)]
+/// The content of a record, together with its type.
+///
+/// Hostnames (of MX, CNAME, ALIAS and NS records) compare ignoring ASCII case, as DNS does.
+#[derive(Debug, Clone, IntoStaticStr)]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum Content {
     A(Ipv4Addr),
@@ -55,13 +92,82 @@ pub enum Content {
     Ns(String),
     Aaaa(Ipv6Addr),
     Srv(String),
-    Tlsa(String),
+    Tlsa(TlsaRecord),
     Caa(String),
-    Https(String),
-    Svcb(String),
+    Https(SvcbRecord),
+    Svcb(SvcbRecord),
 }
 
 impl Content {
+    pub fn a(addr: Ipv4Addr) -> Self {
+        Content::A(addr)
+    }
+
+    pub fn mx(host: impl Into<String>) -> Self {
+        Content::Mx(host.into())
+    }
+
+    pub fn cname(target: impl Into<String>) -> Self {
+        Content::Cname(target.into())
+    }
+
+    pub fn alias(target: impl Into<String>) -> Self {
+        Content::Alias(target.into())
+    }
+
+    /// Builds a TXT record. A value given as quoted character-strings is joined, as Porkbun
+    /// does when storing it, so that it compares equal to the record read back.
+    pub fn txt(value: impl Into<String>) -> Self {
+        Content::Txt(unquote_txt(&value.into()))
+    }
+
+    pub fn ns(host: impl Into<String>) -> Self {
+        Content::Ns(host.into())
+    }
+
+    pub fn aaaa(addr: Ipv6Addr) -> Self {
+        Content::Aaaa(addr)
+    }
+
+    pub fn srv(value: impl Into<String>) -> Self {
+        Content::Srv(value.into())
+    }
+
+    pub fn tlsa(record: TlsaRecord) -> Self {
+        Content::Tlsa(record)
+    }
+
+    pub fn caa(value: impl Into<String>) -> Self {
+        Content::Caa(value.into())
+    }
+
+    pub fn https(record: SvcbRecord) -> Self {
+        Content::Https(record)
+    }
+
+    pub fn svcb(record: SvcbRecord) -> Self {
+        Content::Svcb(record)
+    }
+
+    /// Builds an A or AAAA record depending on the address family.
+    pub fn from_ip(addr: IpAddr) -> Self {
+        addr.into()
+    }
+
+    /// Whether this is a TXT record holding an SPF policy, i.e. one starting with `v=spf1`.
+    pub fn is_spf(&self) -> bool {
+        match self {
+            Content::Txt(value) => {
+                let value = unquote_txt(value);
+                value
+                    .get(..6)
+                    .is_some_and(|version| version.eq_ignore_ascii_case("v=spf1"))
+                    && value[6..].chars().next().is_none_or(|c| c == ' ')
+            }
+            _ => false,
+        }
+    }
+
     pub fn type_as_str(&self) -> &'static str {
         self.into()
     }
@@ -72,35 +178,75 @@ impl Content {
             Content::Mx(value) => value.clone(),
             Content::Cname(value) => value.clone(),
             Content::Alias(value) => value.clone(),
-            Content::Txt(value) => value.clone(),
+            Content::Txt(value) => quote_txt(value),
             Content::Ns(value) => value.clone(),
             Content::Aaaa(addr) => addr.to_string(),
             Content::Srv(value) => value.clone(),
-            Content::Tlsa(value) => value.clone(),
+            Content::Tlsa(value) => value.to_string(),
             Content::Caa(value) => value.clone(),
-            Content::Https(value) => value.clone(),
-            Content::Svcb(value) => value.clone(),
+            Content::Https(value) => value.to_string(),
+            Content::Svcb(value) => value.to_string(),
         }
     }
 
-    pub fn from(type_: &Type, content: &str) -> Result<Content, std::net::AddrParseError> {
+    pub fn from(type_: &Type, content: &str) -> Result<Content, ContentError> {
         Ok(match type_ {
-            Type::A => Content::A(content.parse()?),
+            Type::A => Content::A(content.trim().parse()?),
             Type::Mx => Content::Mx(content.to_string()),
             Type::Cname => Content::Cname(content.to_string()),
             Type::Alias => Content::Alias(content.to_string()),
-            Type::Txt => Content::Txt(content.to_string()),
+            Type::Txt => Content::Txt(unquote_txt(content)),
             Type::Ns => Content::Ns(content.to_string()),
-            Type::Aaaa => Content::Aaaa(content.parse()?),
+            Type::Aaaa => Content::Aaaa(content.trim().parse()?),
             Type::Srv => Content::Srv(content.to_string()),
-            Type::Tlsa => Content::Tlsa(content.to_string()),
+            Type::Tlsa => Content::Tlsa(content.parse()?),
             Type::Caa => Content::Caa(content.to_string()),
-            Type::Https => Content::Https(content.to_string()),
-            Type::Svcb => Content::Svcb(content.to_string()),
+            Type::Https => Content::Https(content.parse()?),
+            Type::Svcb => Content::Svcb(content.parse()?),
         })
     }
 }
 
+impl Ord for Content {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn cmp_hostnames(a: &str, b: &str) -> Ordering {
+            a.bytes()
+                .map(|b| b.to_ascii_lowercase())
+                .cmp(b.bytes().map(|b| b.to_ascii_lowercase()))
+        }
+
+        match (self, other) {
+            (Content::A(a), Content::A(b)) => a.cmp(b),
+            (Content::Mx(a), Content::Mx(b)) => cmp_hostnames(a, b),
+            (Content::Cname(a), Content::Cname(b)) => cmp_hostnames(a, b),
+            (Content::Alias(a), Content::Alias(b)) => cmp_hostnames(a, b),
+            (Content::Txt(a), Content::Txt(b)) => unquote_txt(a).cmp(&unquote_txt(b)),
+            (Content::Ns(a), Content::Ns(b)) => cmp_hostnames(a, b),
+            (Content::Aaaa(a), Content::Aaaa(b)) => a.cmp(b),
+            (Content::Srv(a), Content::Srv(b)) => a.cmp(b),
+            (Content::Tlsa(a), Content::Tlsa(b)) => a.cmp(b),
+            (Content::Caa(a), Content::Caa(b)) => a.cmp(b),
+            (Content::Https(a), Content::Https(b)) => a.cmp(b),
+            (Content::Svcb(a), Content::Svcb(b)) => a.cmp(b),
+            _ => Type::from(self).cmp(&Type::from(other)),
+        }
+    }
+}
+
+impl PartialOrd for Content {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Content {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Content {}
+
 impl From<IpAddr> for Content {
     fn from(value: IpAddr) -> Self {
         match value {
@@ -110,6 +256,17 @@ impl From<IpAddr> for Content {
     }
 }
 
+/// Parses a type name, ignoring case, like `"AAAA"` or `"txt"`.
+impl FromStr for Type {
+    type Err = ContentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Type::all()
+            .find(|type_| type_.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ContentError::UnsupportedType(s.to_string()))
+    }
+}
+
 impl<'de> Deserialize<'de> for Content {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -117,19 +274,296 @@ impl<'de> Deserialize<'de> for Content {
     {
         use serde::de::Error;
 
+        // Long TXT values may come back as a list of their strings rather than a single one.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            String(String),
+            Strings(Vec<String>),
+        }
+
         #[derive(Deserialize)]
         struct ContentDeserializable {
             #[serde(rename = "type")]
             type_: Type,
-            content: String,
+            content: Value,
         }
 
-        ContentDeserializable::deserialize(deserializer)
-            .and_then(|c| Content::from(&c.type_, &c.content).map_err(D::Error::custom))
+        ContentDeserializable::deserialize(deserializer).and_then(|c| {
+            let content = match c.content {
+                Value::String(content) => content,
+                Value::Strings(strings) => strings.concat(),
+            };
+            match c.type_ {
+                Type::Tlsa => TlsaRecord::parse_fields(&content).map(Content::Tlsa),
+                Type::Https => SvcbRecord::parse_fields(&content).map(Content::Https),
+                Type::Svcb => SvcbRecord::parse_fields(&content).map(Content::Svcb),
+                type_ => Content::from(&type_, &content),
+            }
+            .map_err(D::Error::custom)
+        })
+    }
+}
+
+impl Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Content", 2)?;
+        state.serialize_field("type", self.type_as_str())?;
+        state.serialize_field("content", &self.value_to_string())?;
+        state.end()
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// The longest character-string a TXT record can hold, in bytes.
+pub const TXT_CHUNK_LEN: usize = 255;
+
+/// Formats a TXT value for Porkbun.
+///
+/// Values that fit in a single character-string are sent unchanged. Longer values (DKIM keys,
+/// for example) are split into quoted, escaped chunks of at most [`TXT_CHUNK_LEN`] bytes.
+pub fn quote_txt(value: &str) -> String {
+    if value.len() <= TXT_CHUNK_LEN {
+        return value.to_string();
+    }
+
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars() {
+        if chunk.len() + c.len_utf8() > TXT_CHUNK_LEN {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+    }
+    chunks.push(chunk);
+
+    chunks
+        .iter()
+        .map(|chunk| format!("\"{}\"", chunk.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Joins a TXT value made of quoted character-strings back into a single value.
+///
+/// Anything that isn't a sequence of quoted strings is returned unchanged.
+pub fn unquote_txt(value: &str) -> String {
+    fn parse(value: &str) -> Option<String> {
+        let mut joined = String::new();
+        let mut chars = value.trim().chars();
+        loop {
+            match chars.next() {
+                None => return Some(joined),
+                Some('"') => {}
+                Some(c) if c.is_whitespace() => continue,
+                Some(_) => return None,
+            }
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => joined.push(chars.next()?),
+                    c => joined.push(c),
+                }
+            }
+        }
+    }
+
+    if value.trim_start().starts_with('"') {
+        parse(value).unwrap_or_else(|| value.to_string())
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TlsaRecord {
+    pub usage: u8,
+    pub selector: u8,
+    pub matching_type: u8,
+    pub data: String,
+}
+
+impl TlsaRecord {
+    /// Parses the fields of a TLSA record without checking their ranges, for reading records
+    /// Porkbun already stores: one odd record shouldn't make a whole zone unreadable.
+    ///
+    /// The certificate data may be split by whitespace, as the presentation format allows.
+    pub(crate) fn parse_fields(s: &str) -> Result<Self, ContentError> {
+        let invalid = |reason| ContentError::InvalidTlsa(s.to_string(), reason);
+
+        let mut fields = s.split_whitespace();
+        let mut number = |reason| {
+            fields
+                .next()
+                .ok_or_else(|| invalid("expected 4 fields"))?
+                .parse()
+                .map_err(|_| invalid(reason))
+        };
+        let usage = number("usage must be between 0 and 3")?;
+        let selector = number("selector must be 0 or 1")?;
+        let matching_type = number("matching type must be between 0 and 2")?;
+        let data = fields.collect::<String>();
+        if data.is_empty() {
+            return Err(invalid("expected 4 fields"));
+        }
+
+        Ok(TlsaRecord {
+            usage,
+            selector,
+            matching_type,
+            data,
+        })
+    }
+}
+
+impl FromStr for TlsaRecord {
+    type Err = ContentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| ContentError::InvalidTlsa(s.to_string(), reason);
+
+        let record = TlsaRecord::parse_fields(s)?;
+        if record.usage > 3 {
+            return Err(invalid("usage must be between 0 and 3"));
+        }
+        if record.selector > 1 {
+            return Err(invalid("selector must be 0 or 1"));
+        }
+        if record.matching_type > 2 {
+            return Err(invalid("matching type must be between 0 and 2"));
+        }
+        if record.data.len() % 2 != 0 || !record.data.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid("certificate data must be hex"));
+        }
+
+        Ok(record)
+    }
+}
+
+impl fmt::Display for TlsaRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.usage, self.selector, self.matching_type, self.data
+        )
+    }
+}
+
+/// The `priority target params` form shared by SVCB and HTTPS records.
+///
+/// Parameters without a value (like `no-default-alpn`) have an empty value.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SvcbRecord {
+    pub priority: u16,
+    pub target: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl SvcbRecord {
+    /// Parses an SVCB or HTTPS record for reading records Porkbun already stores, accepting a
+    /// quote left open at the end and parameters without a key, which [`SvcbRecord::from_str`]
+    /// rejects: one odd record shouldn't make a whole zone unreadable.
+    pub(crate) fn parse_fields(s: &str) -> Result<Self, ContentError> {
+        let invalid = |reason| ContentError::InvalidSvcb(s.to_string(), reason);
+
+        let mut fields = split_quoted(s).0.into_iter();
+        let priority = fields
+            .next()
+            .ok_or_else(|| invalid("missing priority"))?
+            .parse()
+            .map_err(|_| invalid("priority must be between 0 and 65535"))?;
+        let target = fields.next().ok_or_else(|| invalid("missing target"))?;
+        let params = fields
+            .map(|param| {
+                let (key, value) = param.split_once('=').unwrap_or((&param, ""));
+                (key.to_string(), value.to_string())
+            })
+            .collect();
+
+        Ok(SvcbRecord {
+            priority,
+            target,
+            params,
+        })
+    }
+}
+
+impl FromStr for SvcbRecord {
+    type Err = ContentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| ContentError::InvalidSvcb(s.to_string(), reason);
+
+        if !split_quoted(s).1 {
+            return Err(invalid("unterminated quote"));
+        }
+        let record = SvcbRecord::parse_fields(s)?;
+        if record.params.iter().any(|(key, _)| key.is_empty()) {
+            return Err(invalid("parameter is missing a key"));
+        }
+
+        Ok(record)
+    }
+}
+
+impl fmt::Display for SvcbRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.priority, self.target)?;
+        for (key, value) in &self.params {
+            if value.is_empty() {
+                write!(f, " {key}")?;
+            } else if value.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+                let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, " {key}=\"{value}\"")?;
+            } else {
+                write!(f, " {key}={value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits `s` on whitespace outside of double quotes, dropping the quotes and undoing backslash
+/// escapes within them, so that `alpn="h2, h3"` stays one field. Also returns whether every quote
+/// was closed; an open one runs to the end of `s`.
+fn split_quoted(s: &str) -> (Vec<String>, bool) {
+    let mut fields = Vec::new();
+    let mut field = None::<String>;
+    let mut quoted = false;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                field.get_or_insert_with(String::new);
+            }
+            '\\' if quoted => field.get_or_insert_with(String::new).extend(chars.next()),
+            c if c.is_whitespace() && !quoted => fields.extend(field.take()),
+            c => field.get_or_insert_with(String::new).push(c),
+        }
+    }
+    fields.extend(field);
+
+    (fields, !quoted)
+}
+
+/// The fields Porkbun is known to send for a record.
+pub(crate) const RECORD_FIELDS: &[&str] =
+    &["id", "name", "type", "content", "ttl", "prio", "notes"];
+
+/// A DNS record as returned by Porkbun.
+///
+/// Porkbun doesn't report when a record was created or last modified, so staleness can't be
+/// judged from a `Record`; callers that need it have to track it themselves.
+///
+/// Serializing produces the same shape Porkbun sends, except that `id`, `ttl` and `prio` are
+/// written as integers rather than strings. Both forms deserialize back into a `Record`.
+///
+/// Fields other than the ones Porkbun is known to send are ignored when deserializing.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Record {
     #[serde(deserialize_with = "deserialize_to_i64")]
     pub id: i64,
@@ -143,6 +577,186 @@ pub struct Record {
     pub notes: Option<String>,
 }
 
+impl Record {
+    /// The record's full name without a trailing dot, for comparing with names that may be
+    /// written either way, like `host.example.com.`.
+    pub fn fqdn(&self) -> &str {
+        self.name.strip_suffix('.').unwrap_or(&self.name)
+    }
+
+    /// The record's type, as given by its content.
+    pub fn record_type(&self) -> Type {
+        Type::from(&self.content)
+    }
+
+    /// Takes the record's content, e.g. to create the same record on another domain with
+    /// [`Client::create_dns`](crate::Client::create_dns), passing along `ttl` and `prio`.
+    pub fn into_content(self) -> Content {
+        self.content
+    }
+
+    /// Whether this record has the given content and, where they are `Some`, TTL and priority.
+    /// The ID, name and notes aren't compared.
+    pub fn matches(&self, content: &Content, ttl: Option<i64>, prio: Option<i64>) -> bool {
+        self.content == *content
+            && ttl.is_none_or(|ttl| self.ttl == ttl)
+            && prio.is_none_or(|prio| self.prio == Some(prio))
+    }
+}
+
+/// Records are ordered by name, then type, then content, so that sorted lists read naturally.
+/// The remaining fields only break ties.
+impl Ord for Record {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| self.record_type().cmp(&other.record_type()))
+            .then_with(|| self.content.cmp(&other.content))
+            .then_with(|| self.id.cmp(&other.id))
+            .then_with(|| self.ttl.cmp(&other.ttl))
+            .then_with(|| self.prio.cmp(&other.prio))
+            .then_with(|| self.notes.cmp(&other.notes))
+    }
+}
+
+impl PartialOrd for Record {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A difference between two sets of records, matched up by ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordDiff {
+    /// A record that is only in the new set.
+    Added(Record),
+    /// A record that is only in the old set.
+    Removed(Record),
+    /// A record whose name, content, TTL, priority or notes differ between the sets.
+    Changed { old: Record, new: Record },
+}
+
+/// Compares `old` with `new`, returning the added and changed records in the order of `new`,
+/// followed by the removed ones in the order of `old`.
+pub fn diff(old: &[Record], new: &[Record]) -> Vec<RecordDiff> {
+    let old_by_id = old
+        .iter()
+        .map(|record| (record.id, record))
+        .collect::<HashMap<_, _>>();
+    let new_by_id = new
+        .iter()
+        .map(|record| (record.id, record))
+        .collect::<HashMap<_, _>>();
+
+    let mut diffs = new
+        .iter()
+        .filter_map(|record| match old_by_id.get(&record.id) {
+            None => Some(RecordDiff::Added(record.clone())),
+            Some(old) if *old != record => Some(RecordDiff::Changed {
+                old: (*old).clone(),
+                new: record.clone(),
+            }),
+            Some(_) => None,
+        })
+        .collect::<Vec<_>>();
+    diffs.extend(
+        old.iter()
+            .filter(|record| !new_by_id.contains_key(&record.id))
+            .map(|record| RecordDiff::Removed(record.clone())),
+    );
+
+    diffs
+}
+
+/// Finds the records in `records` that a record of type `type_` at `name` would clash with,
+/// because one of them is a CNAME and DNS doesn't allow a CNAME alongside other records.
+///
+/// `name` is a full name, compared with [`Record::fqdn`] ignoring ASCII case and a trailing dot.
+pub fn cname_conflicts<'a>(
+    records: &'a [Record],
+    name: &str,
+    type_: &Type,
+) -> impl Iterator<Item = &'a Record> {
+    let name = name.strip_suffix('.').unwrap_or(name).to_string();
+    let is_cname = *type_ == Type::Cname;
+
+    // Exactly one of the two has to be a CNAME; two CNAMEs are a duplicate, not a conflict.
+    records.iter().filter(move |record| {
+        record.fqdn().eq_ignore_ascii_case(&name)
+            && is_cname != (record.record_type() == Type::Cname)
+    })
+}
+
+/// Formats `records` as a BIND zone file for the root domain `origin`, e.g. for backing up a
+/// zone or moving it elsewhere.
+///
+/// Names are written relative to `origin` and hostnames are made absolute. ALIAS records, which
+/// aren't standard DNS, are written as they are and may need converting for other servers.
+pub fn to_zonefile(origin: &str, records: &[Record]) -> String {
+    use std::fmt::Write as _;
+
+    let origin = origin.strip_suffix('.').unwrap_or(origin);
+    let mut records = records.iter().collect::<Vec<_>>();
+    records.sort();
+
+    let mut zone = format!("$ORIGIN {origin}.\n");
+    for record in records {
+        let fqdn = record.fqdn();
+        let prefix = fqdn
+            .len()
+            .checked_sub(origin.len())
+            .filter(|end| fqdn.is_char_boundary(*end) && fqdn[*end..].eq_ignore_ascii_case(origin))
+            .map(|end| &fqdn[..end]);
+        let name = match prefix {
+            Some("") => "@".to_string(),
+            Some(prefix) if prefix.len() > 1 && prefix.ends_with('.') => {
+                prefix[..prefix.len() - 1].to_string()
+            }
+            _ => format!("{fqdn}."),
+        };
+
+        let _ = writeln!(
+            zone,
+            "{name}\t{}\tIN\t{}\t{}",
+            record.ttl,
+            record.content.type_as_str(),
+            zonefile_rdata(record),
+        );
+    }
+
+    zone
+}
+
+fn zonefile_rdata(record: &Record) -> String {
+    fn absolute(host: &str) -> String {
+        if host.ends_with('.') {
+            host.to_string()
+        } else {
+            format!("{host}.")
+        }
+    }
+
+    let prio = record.prio.unwrap_or(0);
+    match &record.content {
+        Content::Mx(host) => format!("{prio} {}", absolute(host)),
+        Content::Cname(host) | Content::Alias(host) | Content::Ns(host) => absolute(host),
+        Content::Txt(value) if value.len() <= TXT_CHUNK_LEN => {
+            format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+        // Porkbun keeps the priority of SRV records apart from the weight, port and target.
+        Content::Srv(value) => match value.rsplit_once(' ') {
+            Some((fields, target)) => format!("{prio} {fields} {}", absolute(target)),
+            None => format!("{prio} {value}"),
+        },
+        Content::Https(svcb) | Content::Svcb(svcb) => SvcbRecord {
+            target: absolute(&svcb.target),
+            ..svcb.clone()
+        }
+        .to_string(),
+        content => content.value_to_string(),
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(untagged)]
 enum StringOrI64 {
@@ -0,0 +1,58 @@
+use std::sync::{Arc, Mutex};
+
+use url::Url;
+
+use crate::{ApiError, ResponseMeta};
+
+/// Sends requests to Porkbun on behalf of a [`Client`](crate::Client).
+///
+/// The default transport uses reqwest. Another one can be set with
+/// [`ClientBuilder::transport`](crate::ClientBuilder::transport), e.g. to check the requests a
+/// test makes and answer them with canned responses.
+pub trait Transport: Send + Sync {
+    /// Posts `body` as JSON to `url` and returns the response body as JSON.
+    ///
+    /// Responses with `"status": "ERROR"` should be returned as they are; the client turns them
+    /// into [`ApiError::Api`] or one of the more specific variants for Porkbun's messages. If such
+    /// a response came with an HTTP error status, the transport may instead return that error
+    /// itself with the status set, so that e.g. [`ApiError::is_transient`] can take it into
+    /// account.
+    fn post_json(&self, url: &Url, body: &serde_json::Value)
+        -> Result<serde_json::Value, ApiError>;
+}
+
+pub(crate) struct ReqwestTransport {
+    pub(crate) client: reqwest::blocking::Client,
+    pub(crate) last_response_meta: Arc<Mutex<Option<ResponseMeta>>>,
+}
+
+impl Transport for ReqwestTransport {
+    fn post_json(
+        &self,
+        url: &Url,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, ApiError> {
+        let resp = self.client.post(url.clone()).json(body).send()?;
+
+        *self
+            .last_response_meta
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(ResponseMeta::from_response(&resp));
+
+        let status = resp.status();
+        let status_err = resp.error_for_status_ref().err();
+        let body = resp.text()?;
+        log::trace!("{status} from {url}: {body}");
+        let body = serde_json::from_str::<serde_json::Value>(&body);
+
+        match (body, status_err) {
+            // Porkbun's message says more than the HTTP status, but both are kept.
+            (Ok(body), Some(_)) if body["status"] == "ERROR" => {
+                let message = body["message"].as_str().unwrap_or("unknown error");
+                Err(ApiError::from_message(message, Some(status)))
+            }
+            (_, Some(err)) => Err(err.into()),
+            (body, None) => Ok(body?),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use serde::Deserialize;
+use thiserror::Error as ThisError;
+
+use crate::MIN_TTL;
+
+#[derive(ThisError, Debug, PartialEq, Eq)]
+pub enum TtlError {
+    #[error("invalid TTL {0:?}")]
+    Invalid(String),
+    #[error("TTL {0} is below the minimum of {MIN_TTL}")]
+    BelowMinimum(i64),
+}
+
+/// Parses a TTL given either as seconds (`3600`) or as a duration made of numbers with the units
+/// `s`, `m`, `h`, `d` and `w` (`1h`, `1h30m`), returning it in seconds.
+pub fn parse_ttl(input: &str) -> Result<i64, TtlError> {
+    let invalid = || TtlError::Invalid(input.to_string());
+    let trimmed = input.trim();
+
+    let ttl = if let Ok(seconds) = trimmed.parse() {
+        seconds
+    } else if trimmed.is_empty() {
+        return Err(invalid());
+    } else {
+        let mut total = 0i64;
+        let mut rest = trimmed;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let (number, tail) = rest.split_at(digits);
+            let mut units = tail.chars();
+            let unit = match units.next() {
+                Some('s') => 1,
+                Some('m') => 60,
+                Some('h') => 60 * 60,
+                Some('d') => 24 * 60 * 60,
+                Some('w') => 7 * 24 * 60 * 60,
+                _ => return Err(invalid()),
+            };
+            let number = number.parse::<i64>().map_err(|_| invalid())?;
+            total = number
+                .checked_mul(unit)
+                .and_then(|seconds| total.checked_add(seconds))
+                .ok_or_else(invalid)?;
+            rest = units.as_str();
+        }
+        total
+    };
+
+    if ttl < MIN_TTL {
+        return Err(TtlError::BelowMinimum(ttl));
+    }
+
+    Ok(ttl)
+}
+
+/// Deserializes a TTL written as a number of seconds or as a string accepted by [`parse_ttl`],
+/// for use with `#[serde(deserialize_with = "...")]` in configuration types.
+pub fn deserialize_ttl<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Ttl {
+        Seconds(i64),
+        String(String),
+    }
+
+    let ttl = match Ttl::deserialize(deserializer)? {
+        Ttl::Seconds(seconds) => seconds.to_string(),
+        Ttl::String(ttl) => ttl,
+    };
+
+    parse_ttl(&ttl).map_err(D::Error::custom)
+}
@@ -0,0 +1,2246 @@
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::{Duration, Instant},
+};
+
+use addr::{domain, parse_dns_name, parse_domain_name};
+use hamsando::{
+    dnssec::DnssecRecord,
+    record::{Content, Record, RecordDiff, Type},
+    ApiError, Client, ClientBuilderError, EnsureOutcome,
+};
+use mockito::{Matcher, Mock, Server, ServerGuard};
+use serde_json::json;
+
+fn client(server: &ServerGuard) -> Client {
+    Client::builder()
+        .endpoint_str(&format!("{}/api/json/v3/", server.url()))
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .build()
+        .unwrap()
+}
+
+fn name(name: &str) -> domain::Name<'_> {
+    parse_domain_name(name).unwrap()
+}
+
+fn auth(extra: serde_json::Value) -> Matcher {
+    let mut body = json!({
+        "apikey": "pk1_test",
+        "secretapikey": "sk1_test",
+    });
+    if let serde_json::Value::Object(extra) = extra {
+        for (key, value) in extra {
+            body[key] = value;
+        }
+    }
+    Matcher::Json(body)
+}
+
+fn mock(server: &mut ServerGuard, path: &str, body: Matcher, response: serde_json::Value) -> Mock {
+    server
+        .mock("POST", path)
+        .match_body(body)
+        .with_header("content-type", "application/json")
+        .with_body(response.to_string())
+        .create()
+}
+
+#[test]
+fn test_auth() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/ping",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}),
+    );
+
+    let ip = client(&server).test_auth().unwrap();
+
+    mock.assert();
+    assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)));
+}
+
+#[test]
+fn create_dns() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        auth(json!({
+            "name": "www",
+            "type": "A",
+            "content": "192.0.2.1",
+            "ttl": 600,
+        })),
+        json!({"status": "SUCCESS", "id": "106926659"}),
+    );
+
+    let id = client(&server)
+        .create_dns(
+            &name("www.example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            Some(600),
+            None,
+        )
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(id, 106926659);
+}
+
+#[test]
+fn create_dns_record() {
+    let mut server = Server::new();
+    let create = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        auth(json!({"name": "www", "type": "A", "content": "192.0.2.1"})),
+        json!({"status": "SUCCESS", "id": "106926659"}),
+    );
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/106926659",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926659",
+                "name": "www.example.com",
+                "type": "A",
+                "content": "192.0.2.1",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+
+    let record = client(&server)
+        .create_dns_record(
+            &name("www.example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    create.assert();
+    retrieve.assert();
+    assert_eq!(record.id, 106926659);
+    assert_eq!(record.name, "www.example.com");
+    assert_eq!(record.ttl, 600);
+}
+
+#[test]
+fn import_records() {
+    let mut server = Server::new();
+    let apex = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        auth(json!({"type": "MX", "content": "mail.example.com", "prio": 10})),
+        json!({"status": "SUCCESS", "id": "1"}),
+    );
+    let www = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        auth(json!({"name": "www", "type": "A", "content": "192.0.2.1", "ttl": 3600})),
+        json!({"status": "ERROR", "message": "Duplicate record."}),
+    );
+
+    let results = client(&server)
+        .import_records(
+            &name("example.com"),
+            &[
+                (
+                    "@".to_string(),
+                    Content::mx("mail.example.com"),
+                    None,
+                    Some(10),
+                ),
+                (
+                    "www".to_string(),
+                    Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+                    Some(3600),
+                    None,
+                ),
+                (
+                    "ftp".to_string(),
+                    Content::cname("example.com"),
+                    Some(60),
+                    None,
+                ),
+            ],
+        )
+        .unwrap();
+
+    apex.assert();
+    www.assert();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap(), &1);
+    assert!(matches!(&results[1], Err(ApiError::Api { .. })));
+    assert!(matches!(&results[2], Err(ApiError::InvalidTtl(60))));
+}
+
+#[test]
+fn create_dns_wildcard() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        auth(json!({"name": "*", "type": "A", "content": "192.0.2.1"})),
+        json!({"status": "SUCCESS", "id": "106926659"}),
+    );
+
+    client(&server)
+        .create_dns(
+            &parse_dns_name("*.example.com").unwrap(),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn retrieve_dns_by_name_type_wildcard() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/A/*",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926659",
+                "name": "*.example.com",
+                "type": "A",
+                "content": "192.0.2.1",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+
+    let records = client(&server)
+        .retrieve_dns_by_name_type(&parse_dns_name("*.example.com").unwrap(), &Type::A)
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(records[0].name, "*.example.com");
+}
+
+#[test]
+fn create_dns_cname_at_apex() {
+    let mut server = Server::new();
+    let mock = server.mock("POST", Matcher::Any).expect(0).create();
+
+    let err = client(&server)
+        .create_dns(
+            &name("example.com"),
+            &Content::cname("example.net"),
+            None,
+            None,
+        )
+        .unwrap_err();
+
+    mock.assert();
+    assert!(
+        matches!(&err, ApiError::CnameAtApex(root) if root == "example.com"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn create_dns_idn() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/create/xn--caf-dma.com",
+        auth(json!({
+            "name": "xn--bcher-kva",
+            "type": "A",
+            "content": "192.0.2.1",
+        })),
+        json!({"status": "SUCCESS", "id": "106926659"}),
+    );
+
+    client(&server)
+        .create_dns(
+            &name("bücher.café.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn edit_dns() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/edit/example.com/106926659",
+        auth(json!({
+            "name": "mail",
+            "type": "MX",
+            "content": "mx.example.com",
+            "prio": 10,
+        })),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .edit_dns(
+            &name("mail.example.com"),
+            106926659,
+            &Content::Mx("mx.example.com".to_string()),
+            None,
+            Some(10),
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn edit_dns_by_name_type() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/editByNameType/example.com/TXT/www",
+        auth(json!({"content": "hello"})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .edit_dns_by_name_type(
+            &name("www.example.com"),
+            &Content::Txt("hello".to_string()),
+            None,
+            None,
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn delete_dns() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/delete/example.com/106926659",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .delete_dns(&name("example.com"), 106926659)
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn delete_dns_already_gone() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/dns/delete/example.com/106926659")
+        .match_body(auth(json!({})))
+        .with_body(
+            json!({"status": "ERROR", "message": "Delete error: Record not found."}).to_string(),
+        )
+        .expect(2)
+        .create();
+
+    let client = client(&server);
+    client.delete_dns(&name("example.com"), 106926659).unwrap();
+    let err = client
+        .delete_dns_strict(&name("example.com"), 106926659)
+        .unwrap_err();
+
+    mock.assert();
+    assert!(matches!(err, ApiError::Api { .. }), "{err:?}");
+}
+
+#[test]
+fn delete_dns_domain_not_found() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/delete/example.com/106926659",
+        auth(json!({})),
+        json!({"status": "ERROR", "message": "Domain not found."}),
+    );
+
+    let err = client(&server)
+        .delete_dns(&name("example.com"), 106926659)
+        .unwrap_err();
+
+    mock.assert();
+    assert!(
+        matches!(&err, ApiError::Api { message, .. } if message == "Domain not found."),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn delete_dns_bulk() {
+    let mut server = Server::new();
+    let first = mock(
+        &mut server,
+        "/api/json/v3/dns/delete/example.com/1",
+        auth(json!({})),
+        json!({"status": "ERROR", "message": "Invalid API key."}),
+    );
+    let second = mock(
+        &mut server,
+        "/api/json/v3/dns/delete/example.com/2",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    let results = client(&server)
+        .delete_dns_bulk(&name("example.com"), &[1, 2])
+        .unwrap();
+
+    first.assert();
+    second.assert();
+    assert!(matches!(&results[0], Err(ApiError::Api { .. })));
+    assert!(results[1].is_ok());
+}
+
+#[test]
+fn delete_dns_by_name_type() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/deleteByNameType/example.com/AAAA/www",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .delete_dns_by_name_type(&name("www.example.com"), &Type::Aaaa)
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn delete_dns_by_name_type_counted() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/TXT/www",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "1",
+                    "name": "www.example.com",
+                    "type": "TXT",
+                    "content": "first",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+                {
+                    "id": "2",
+                    "name": "www.example.com",
+                    "type": "TXT",
+                    "content": "second",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+    let delete = mock(
+        &mut server,
+        "/api/json/v3/dns/deleteByNameType/example.com/TXT/www",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    let count = client(&server)
+        .delete_dns_by_name_type_counted(&name("www.example.com"), &Type::Txt)
+        .unwrap();
+
+    retrieve.assert();
+    delete.assert();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn retrieve_dns() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "106926652",
+                    "name": "example.com",
+                    "type": "A",
+                    "content": "192.0.2.1",
+                    "ttl": "600",
+                    "prio": "0",
+                    "notes": "",
+                },
+                {
+                    "id": "106926653",
+                    "name": "www.example.com",
+                    "type": "CNAME",
+                    "content": "example.com",
+                    "ttl": "3600",
+                    "prio": null,
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+
+    let records = client(&server)
+        .retrieve_dns(&name("example.com"), None)
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].id, 106926652);
+    assert_eq!(records[0].name, "example.com");
+    assert_eq!(records[0].content, Content::A(Ipv4Addr::new(192, 0, 2, 1)));
+    assert_eq!(records[0].ttl, 600);
+    assert_eq!(records[0].prio, Some(0));
+    assert_eq!(
+        records[1].content,
+        Content::Cname("example.com".to_string())
+    );
+    assert_eq!(records[1].prio, None);
+}
+
+#[test]
+fn count_dns() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "1",
+                    "name": "example.com",
+                    "type": "A",
+                    "content": "192.0.2.1",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+                {
+                    "id": "2",
+                    "name": "www.example.com",
+                    "type": "CNAME",
+                    "content": "example.com",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+
+    let count = client(&server).count_dns(&name("example.com")).unwrap();
+
+    mock.assert();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn diff_against() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "1",
+                    "name": "example.com",
+                    "type": "A",
+                    "content": "192.0.2.2",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+                {
+                    "id": "3",
+                    "name": "mail.example.com",
+                    "type": "A",
+                    "content": "192.0.2.3",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+    let record = |id: i64, name: &str, addr: Ipv4Addr| Record {
+        id,
+        name: name.to_string(),
+        content: Content::A(addr),
+        ttl: 600,
+        prio: None,
+        notes: None,
+    };
+    let snapshot = [
+        record(1, "example.com", Ipv4Addr::new(192, 0, 2, 1)),
+        record(2, "www.example.com", Ipv4Addr::new(192, 0, 2, 1)),
+    ];
+
+    let diffs = client(&server)
+        .diff_against(&name("example.com"), &snapshot)
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(
+        diffs,
+        [
+            RecordDiff::Changed {
+                old: snapshot[0].clone(),
+                new: record(1, "example.com", Ipv4Addr::new(192, 0, 2, 2)),
+            },
+            RecordDiff::Added(record(3, "mail.example.com", Ipv4Addr::new(192, 0, 2, 3))),
+            RecordDiff::Removed(snapshot[1].clone()),
+        ]
+    );
+}
+
+#[test]
+fn export_zonefile() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "2",
+                    "name": "example.com",
+                    "type": "MX",
+                    "content": "mail.example.com",
+                    "ttl": "600",
+                    "prio": "10",
+                    "notes": null,
+                },
+                {
+                    "id": "1",
+                    "name": "example.com",
+                    "type": "A",
+                    "content": "192.0.2.1",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+
+    let zone = client(&server)
+        .export_zonefile(&name("example.com"))
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(
+        zone,
+        "$ORIGIN example.com.\n\
+         @\t600\tIN\tA\t192.0.2.1\n\
+         @\t600\tIN\tMX\t10 mail.example.com.\n"
+    );
+    assert!(client(&server)
+        .export_zonefile(&name("www.example.com"))
+        .is_err());
+}
+
+#[test]
+fn retrieve_dns_by_type() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "1",
+                    "name": "example.com",
+                    "type": "MX",
+                    "content": "mail.example.com",
+                    "ttl": "600",
+                    "prio": "10",
+                    "notes": null,
+                },
+                {
+                    "id": "2",
+                    "name": "www.example.com",
+                    "type": "A",
+                    "content": "192.0.2.1",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+                {
+                    "id": "3",
+                    "name": "eu.example.com",
+                    "type": "MX",
+                    "content": "mail.eu.example.com",
+                    "ttl": "600",
+                    "prio": "20",
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+
+    let records = client(&server)
+        .retrieve_dns_by_type(&name("example.com"), &Type::Mx)
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(
+        records.iter().map(|record| record.id).collect::<Vec<_>>(),
+        [1, 3]
+    );
+}
+
+#[test]
+fn retrieve_dns_grouped() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "1",
+                    "name": "example.com",
+                    "type": "MX",
+                    "content": "mail.example.com",
+                    "ttl": "600",
+                    "prio": "10",
+                    "notes": null,
+                },
+                {
+                    "id": "2",
+                    "name": "www.example.com",
+                    "type": "A",
+                    "content": "192.0.2.1",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+                {
+                    "id": "3",
+                    "name": "eu.example.com",
+                    "type": "MX",
+                    "content": "mail.eu.example.com",
+                    "ttl": "600",
+                    "prio": "20",
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+
+    let grouped = client(&server)
+        .retrieve_dns_grouped(&name("example.com"))
+        .unwrap();
+
+    mock.assert();
+    let ids = |type_| {
+        grouped[&type_]
+            .iter()
+            .map(|record| record.id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(grouped.len(), 2);
+    assert_eq!(ids(Type::Mx), [1, 3]);
+    assert_eq!(ids(Type::A), [2]);
+}
+
+#[test]
+fn retrieve_dns_by_id() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/106926652",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "records": []}),
+    );
+
+    let records = client(&server)
+        .retrieve_dns(&name("example.com"), Some(106926652))
+        .unwrap();
+
+    mock.assert();
+    assert!(records.is_empty());
+}
+
+#[test]
+fn retrieve_dns_single() {
+    let mut server = Server::new();
+    let record = |content: &str| {
+        json!({
+            "id": "106926652",
+            "name": "example.com",
+            "type": "A",
+            "content": content,
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        })
+    };
+    let found = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/106926652",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "records": [record("192.0.2.1")]}),
+    );
+    let missing = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/106926653",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "records": []}),
+    );
+    let duplicate = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieve/example.com/106926654",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "records": [record("192.0.2.1"), record("192.0.2.2")]}),
+    );
+
+    let client = client(&server);
+    let domain = name("example.com");
+
+    let record = client.retrieve_dns_single(&domain, 106926652).unwrap();
+    assert_eq!(record.map(|record| record.id), Some(106926652));
+    assert_eq!(
+        client.retrieve_dns_single(&domain, 106926653).unwrap(),
+        None
+    );
+    let err = client.retrieve_dns_single(&domain, 106926654).unwrap_err();
+    assert!(
+        matches!(err, ApiError::DuplicateRecord(106926654)),
+        "{err:?}"
+    );
+
+    found.assert();
+    missing.assert();
+    duplicate.assert();
+}
+
+#[test]
+fn retrieve_dns_rejects_prefix() {
+    let server = Server::new();
+
+    assert!(client(&server)
+        .retrieve_dns(&name("www.example.com"), None)
+        .is_err());
+}
+
+#[test]
+fn retrieve_dns_by_name_type() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/A/www",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926652",
+                "name": "www.example.com",
+                "type": "A",
+                "content": "192.0.2.1",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+
+    let records = client(&server)
+        .retrieve_dns_by_name_type(&name("www.example.com"), &Type::A)
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].name, "www.example.com");
+}
+
+#[test]
+fn get_current() {
+    let mut server = Server::new();
+    let found = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/A/www",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926652",
+                "name": "www.example.com",
+                "type": "A",
+                "content": "192.0.2.1",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+    let empty = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/AAAA/www",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "records": []}),
+    );
+
+    let client = client(&server);
+    let www = name("www.example.com");
+
+    assert_eq!(
+        client.get_current(&www, &Type::A).unwrap(),
+        Some(Content::A(Ipv4Addr::new(192, 0, 2, 1)))
+    );
+    assert_eq!(client.get_current(&www, &Type::Aaaa).unwrap(), None);
+    found.assert();
+    empty.assert();
+}
+
+#[test]
+fn ensure_dns_unchanged() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/A/www",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926652",
+                "name": "www.example.com",
+                "type": "A",
+                "content": "192.0.2.1",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+
+    let outcome = client(&server)
+        .ensure_dns(
+            &name("www.example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            Some(600),
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    assert_eq!(outcome, EnsureOutcome::Unchanged(106926652));
+}
+
+#[test]
+fn ensure_spf_updates_only_spf() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/TXT",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "1",
+                    "name": "example.com",
+                    "type": "TXT",
+                    "content": "google-site-verification=abc",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+                {
+                    "id": "2",
+                    "name": "example.com",
+                    "type": "TXT",
+                    "content": "v=spf1 mx -all",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+    let edit = mock(
+        &mut server,
+        "/api/json/v3/dns/edit/example.com/2",
+        auth(json!({"type": "TXT", "content": "v=spf1 mx include:_spf.example.net -all"})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    let outcome = client(&server)
+        .ensure_spf(
+            &name("example.com"),
+            &Content::txt("v=spf1 mx include:_spf.example.net -all"),
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    edit.assert();
+    assert_eq!(outcome, EnsureOutcome::Updated(2));
+}
+
+#[test]
+fn set_address_record_non_canonical_aaaa() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/AAAA/home",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926652",
+                "name": "home.example.com",
+                "type": "AAAA",
+                "content": "2001:DB8:0:0:0:0:0:1",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+    let edit = server
+        .mock("POST", Matcher::Regex("^/api/json/v3/dns/edit".to_string()))
+        .expect(0)
+        .create();
+
+    let outcome = client(&server)
+        .set_address_record(
+            &name("home.example.com"),
+            "2001:db8::1".parse().unwrap(),
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    edit.assert();
+    assert_eq!(outcome, EnsureOutcome::Unchanged(106926652));
+}
+
+#[test]
+fn ensure_dns_quoted_txt_unchanged() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/TXT",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926652",
+                "name": "example.com",
+                "type": "TXT",
+                "content": "\"google-site-verification=abc\"",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+    let edit = server
+        .mock("POST", Matcher::Regex("^/api/json/v3/dns/edit".to_string()))
+        .expect(0)
+        .create();
+
+    let outcome = client(&server)
+        .ensure_dns(
+            &name("example.com"),
+            &Content::txt("google-site-verification=abc"),
+            None,
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    edit.assert();
+    assert_eq!(outcome, EnsureOutcome::Unchanged(106926652));
+}
+
+#[test]
+fn ensure_dns_quoted_desired_txt_unchanged() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/TXT",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926652",
+                "name": "example.com",
+                "type": "TXT",
+                "content": "google-site-verification=abc",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+    let edit = server
+        .mock("POST", Matcher::Regex("^/api/json/v3/dns/edit".to_string()))
+        .expect(0)
+        .create();
+
+    let outcome = client(&server)
+        .ensure_dns(
+            &name("example.com"),
+            &Content::txt("\"google-site-verification=abc\""),
+            None,
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    edit.assert();
+    assert_eq!(outcome, EnsureOutcome::Unchanged(106926652));
+}
+
+#[test]
+fn ensure_dns_mixed_case_cname_unchanged() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/CNAME/www",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926652",
+                "name": "www.example.com",
+                "type": "CNAME",
+                "content": "target.example.net",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+    let edit = server
+        .mock("POST", Matcher::Regex("^/api/json/v3/dns/edit".to_string()))
+        .expect(0)
+        .create();
+
+    let outcome = client(&server)
+        .ensure_dns(
+            &name("www.example.com"),
+            &Content::cname("Target.Example.NET"),
+            None,
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    edit.assert();
+    assert_eq!(outcome, EnsureOutcome::Unchanged(106926652));
+}
+
+#[test]
+fn ensure_dns_created() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/A/www",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "records": []}),
+    );
+    let create = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        auth(json!({
+            "name": "www",
+            "type": "A",
+            "content": "192.0.2.1",
+        })),
+        json!({"status": "SUCCESS", "id": 106926659}),
+    );
+
+    let outcome = client(&server)
+        .ensure_dns(
+            &name("www.example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    create.assert();
+    assert_eq!(outcome, EnsureOutcome::Created(106926659));
+}
+
+#[test]
+fn ensure_dns_rejects_clamped_ttl() {
+    let mut server = Server::new();
+    let retrieve = server.mock("POST", Matcher::Any).expect(0).create();
+
+    let err = client(&server)
+        .ensure_dns(
+            &name("www.example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            Some(300),
+            None,
+        )
+        .unwrap_err();
+
+    retrieve.assert();
+    assert!(matches!(err, ApiError::InvalidTtl(300)), "{err:?}");
+}
+
+#[test]
+fn get_dnssec_records() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/getDnssecRecords/example.com",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": {
+                "64087": {
+                    "keyTag": "64087",
+                    "alg": "13",
+                    "digestType": "2",
+                    "digest": "15E445BD08128BDC213E25F1C8227DF4CB35186CAC701C1D2E4DBC31193C1D3B",
+                },
+            },
+        }),
+    );
+
+    let records = client(&server)
+        .get_dnssec_records(&name("example.com"))
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].key_tag, 64087);
+    assert_eq!(records[0].alg, 13);
+    assert_eq!(records[0].digest_type, 2);
+    assert_eq!(records[0].max_sig_life, None);
+}
+
+#[test]
+fn create_dnssec_record() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/createDnssecRecord/example.com",
+        auth(json!({
+            "keyTag": "64087",
+            "alg": "13",
+            "digestType": "2",
+            "digest": "15E445BD08128BDC213E25F1C8227DF4CB35186CAC701C1C335B2C406D5530DC",
+            "maxSigLife": "86400",
+            "keyDataFlags": "257",
+            "keyDataProtocol": "3",
+            "keyDataAlgo": "13",
+            "keyDataPubKey": "mdsswUyr3DPW132mOi8V9xESWE8jTo0dxCjjnopKl+GqJxpVXckHAeF+KkxLbxILfDLUT0rAK9iUzy1L53eKGQ==",
+        })),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .create_dnssec_record(
+            &name("example.com"),
+            &DnssecRecord {
+                key_tag: 64087,
+                alg: 13,
+                digest_type: 2,
+                digest: "15E445BD08128BDC213E25F1C8227DF4CB35186CAC701C1C335B2C406D5530DC"
+                    .to_string(),
+                max_sig_life: Some(86400),
+                key_data_flags: Some(257),
+                key_data_protocol: Some(3),
+                key_data_algo: Some(13),
+                key_data_pub_key: Some(
+                    "mdsswUyr3DPW132mOi8V9xESWE8jTo0dxCjjnopKl+GqJxpVXckHAeF+KkxLbxILfDLUT0rAK9iUzy1L53eKGQ=="
+                        .to_string(),
+                ),
+            },
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn get_glue() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/domain/getGlue/example.com",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "hosts": [
+                ["ns1.example.com", {"v4": ["192.0.2.53"], "v6": ["2001:db8::53"]}],
+            ],
+        }),
+    );
+
+    let hosts = client(&server).get_glue(&name("example.com")).unwrap();
+
+    mock.assert();
+    assert_eq!(hosts.len(), 1);
+    assert_eq!(hosts[0].host, "ns1.example.com");
+    assert_eq!(
+        hosts[0].ips,
+        [
+            "192.0.2.53".parse::<IpAddr>().unwrap(),
+            "2001:db8::53".parse().unwrap()
+        ]
+    );
+}
+
+#[test]
+fn create_glue() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/domain/createGlue/example.com/ns1",
+        auth(json!({"ips": ["192.0.2.53", "2001:db8::53"]})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .create_glue(
+            &name("example.com"),
+            "ns1",
+            &[
+                "192.0.2.53".parse().unwrap(),
+                "2001:db8::53".parse().unwrap(),
+            ],
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn update_glue() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/domain/updateGlue/example.com/ns1",
+        auth(json!({"ips": ["192.0.2.54"]})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .update_glue(
+            &name("example.com"),
+            "ns1",
+            &["192.0.2.54".parse().unwrap()],
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn post_raw() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/domain/listAll",
+        auth(json!({"start": "0"})),
+        json!({"status": "SUCCESS", "domains": []}),
+    );
+
+    let resp = client(&server)
+        .post_raw("domain/listAll", json!({"start": "0"}))
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(resp["domains"], json!([]));
+}
+
+#[test]
+fn post_raw_rejects_non_object() {
+    let server = Server::new();
+    let client = client(&server);
+
+    for extra in [json!(["start"]), json!("start=0")] {
+        let err = client.post_raw("domain/listAll", extra).unwrap_err();
+        assert!(matches!(err, ApiError::InvalidExtra(_)), "{err:?}");
+    }
+}
+
+#[test]
+fn pool_options() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/ping")
+        .match_body(auth(json!({})))
+        .with_body(json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}).to_string())
+        .expect(2)
+        .create();
+
+    for timeout in [Some(Duration::from_secs(30)), None] {
+        Client::builder()
+            .endpoint_str(&format!("{}/api/json/v3/", server.url()))
+            .apikey("pk1_test")
+            .secretapikey("sk1_test")
+            .pool_idle_timeout(timeout)
+            .pool_max_idle_per_host(1)
+            .build()
+            .unwrap()
+            .test_auth()
+            .unwrap();
+    }
+
+    mock.assert();
+}
+
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+#[test]
+fn danger_accept_invalid_certs() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/ping",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}),
+    );
+
+    Client::builder()
+        .endpoint_str(&format!("{}/api/json/v3/", server.url()))
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap()
+        .test_auth()
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn endpoint_without_trailing_slash() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/ping",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}),
+    );
+
+    Client::builder()
+        .endpoint_str(&format!("{}/api/json/v3", server.url()))
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .build()
+        .unwrap()
+        .test_auth()
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn edit_dns_multi_label_root() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/edit/example.co.uk/42",
+        auth(json!({
+            "name": "www.staging",
+            "type": "A",
+            "content": "192.0.2.1",
+        })),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .edit_dns(
+            &name("www.staging.example.co.uk"),
+            42,
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn delete_dnssec_record() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/deleteDnssecRecord/example.com/64087",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .delete_dnssec_record(&name("example.com"), 64087)
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn delete_glue_encodes_subdomain() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/domain/deleteGlue/example.com/ns1%3Fx",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .delete_glue(&name("example.com"), "ns1?x")
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn edit_dns_by_name_type_apex() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/editByNameType/example.com/A",
+        auth(json!({"content": "192.0.2.1"})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .edit_dns_by_name_type(
+            &name("example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn delete_dns_by_name_type_apex() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/deleteByNameType/example.com/TXT",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .delete_dns_by_name_type(&name("example.com"), &Type::Txt)
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn base_url_and_api_version() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v4/ping",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}),
+    );
+
+    Client::builder()
+        .base_url(&server.url().parse().unwrap())
+        .api_version("v4")
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .build()
+        .unwrap()
+        .test_auth()
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn edit_dns_full_renames() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/edit/example.com/42",
+        auth(json!({
+            "name": "new",
+            "type": "A",
+            "content": "192.0.2.1",
+        })),
+        json!({"status": "SUCCESS"}),
+    );
+
+    client(&server)
+        .edit_dns_full(
+            &name("old.example.com"),
+            42,
+            Some("new"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn set_address_record_updates_aaaa() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/AAAA/home",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "7",
+                "name": "home.example.com",
+                "type": "AAAA",
+                "content": "2001:db8::1",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+    let edit = mock(
+        &mut server,
+        "/api/json/v3/dns/edit/example.com/7",
+        auth(json!({
+            "name": "home",
+            "type": "AAAA",
+            "content": "2001:db8::2",
+        })),
+        json!({"status": "SUCCESS"}),
+    );
+
+    let outcome = client(&server)
+        .set_address_record(
+            &name("home.example.com"),
+            "2001:db8::2".parse().unwrap(),
+            None,
+        )
+        .unwrap();
+
+    retrieve.assert();
+    edit.assert();
+    assert_eq!(outcome, EnsureOutcome::Updated(7));
+}
+
+#[test]
+fn retrieve_dns_by_name_type_apex() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/TXT",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [{
+                "id": "9",
+                "name": "example.com",
+                "type": "TXT",
+                "content": "v=spf1 -all",
+                "ttl": "600",
+                "prio": null,
+                "notes": null,
+            }],
+        }),
+    );
+
+    let records = client(&server)
+        .retrieve_dns_by_name_type(&name("example.com"), &Type::Txt)
+        .unwrap();
+
+    mock.assert();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].name, "example.com");
+    assert_eq!(records[0].content, Content::Txt("v=spf1 -all".to_string()));
+}
+
+#[test]
+fn rate_limit_paces_requests() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_body(json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}).to_string())
+        .expect(12)
+        .create();
+
+    let client = Client::builder()
+        .endpoint_str(&format!("{}/api/json/v3/", server.url()))
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .rate_limit(10.0)
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    for _ in 0..12 {
+        client.test_auth().unwrap();
+    }
+
+    mock.assert();
+    // The first 10 requests use up the burst; the other 2 wait 100ms each.
+    assert!(start.elapsed() >= Duration::from_millis(180));
+}
+
+#[test]
+fn rate_limit_rejects_invalid_rates() {
+    for per_second in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let err = Client::builder()
+            .apikey("pk1_test")
+            .secretapikey("sk1_test")
+            .rate_limit(per_second)
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(err, ClientBuilderError::InvalidRateLimit(_)),
+            "{err:?}"
+        );
+    }
+}
+
+#[test]
+fn delete_dns_matching_only_deletes_equal_content() {
+    let mut server = Server::new();
+    let retrieve = mock(
+        &mut server,
+        "/api/json/v3/dns/retrieveByNameType/example.com/TXT",
+        auth(json!({})),
+        json!({
+            "status": "SUCCESS",
+            "records": [
+                {
+                    "id": "1",
+                    "name": "example.com",
+                    "type": "TXT",
+                    "content": "v=spf1 -all",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+                {
+                    "id": "2",
+                    "name": "example.com",
+                    "type": "TXT",
+                    "content": "verification=abc",
+                    "ttl": "600",
+                    "prio": null,
+                    "notes": null,
+                },
+            ],
+        }),
+    );
+    let delete = mock(
+        &mut server,
+        "/api/json/v3/dns/delete/example.com/2",
+        auth(json!({})),
+        json!({"status": "SUCCESS"}),
+    );
+
+    let count = client(&server)
+        .delete_dns_matching(&name("example.com"), &Content::txt("verification=abc"))
+        .unwrap();
+
+    retrieve.assert();
+    delete.assert();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn upsert_dns_by_name_type_creates_missing() {
+    let mut server = Server::new();
+    let edit = mock(
+        &mut server,
+        "/api/json/v3/dns/editByNameType/example.com/A/home",
+        Matcher::Any,
+        json!({"status": "ERROR", "message": "Edit error: No records found."}),
+    );
+    let create = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        auth(json!({"name": "home", "type": "A", "content": "192.0.2.1"})),
+        json!({"status": "SUCCESS", "id": "106926659"}),
+    );
+
+    let id = client(&server)
+        .upsert_dns_by_name_type(
+            &name("home.example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    edit.assert();
+    create.assert();
+    assert_eq!(id, Some(106926659));
+}
+
+#[test]
+fn upsert_dns_by_name_type_keeps_other_errors() {
+    let mut server = Server::new();
+    let edit = mock(
+        &mut server,
+        "/api/json/v3/dns/editByNameType/example.com/A/home",
+        Matcher::Any,
+        json!({"status": "ERROR", "message": "Invalid API key."}),
+    );
+    let create = server
+        .mock("POST", "/api/json/v3/dns/create/example.com")
+        .expect(0)
+        .create();
+
+    let err = client(&server)
+        .upsert_dns_by_name_type(
+            &name("home.example.com"),
+            &Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+            None,
+            None,
+        )
+        .unwrap_err();
+
+    edit.assert();
+    create.assert();
+    assert!(matches!(err, ApiError::Api { .. }), "{err:?}");
+}
+
+#[test]
+fn edit_dns_from_record() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/edit/example.com/106926659",
+        auth(json!({
+            "name": "mail",
+            "type": "MX",
+            "content": "mx2.example.com",
+            "ttl": 3600,
+            "prio": 20,
+        })),
+        json!({"status": "SUCCESS"}),
+    );
+    let record = Record {
+        id: 106926659,
+        name: "mail.example.com".to_string(),
+        content: Content::mx("mx.example.com"),
+        ttl: 3600,
+        prio: Some(20),
+        notes: None,
+    };
+
+    client(&server)
+        .edit_dns_from_record(
+            &name("mail.example.com"),
+            &record,
+            &Content::mx("mx2.example.com"),
+        )
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn reqwest_client() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/ping")
+        .match_header("user-agent", "shared-client")
+        .with_body(json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}).to_string())
+        .create();
+
+    Client::builder()
+        .endpoint_str(&format!("{}/api/json/v3/", server.url()))
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .reqwest_client(
+            reqwest::blocking::Client::builder()
+                .user_agent("shared-client")
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap()
+        .test_auth()
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn local_address() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/ping",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "yourIp": "127.0.0.1"}),
+    );
+
+    Client::builder()
+        .endpoint_str(&format!("{}/api/json/v3/", server.url()))
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .local_address(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        .build()
+        .unwrap()
+        .test_auth()
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn set_auto_renew() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/domain/updateAutoRenew/example.com",
+        auth(json!({"status": "on"})),
+        json!({
+            "status": "SUCCESS",
+            "results": {"example.com": {"status": "SUCCESS", "message": "Auto renew status updated."}},
+        }),
+    );
+
+    client(&server)
+        .set_auto_renew(&name("example.com"), true)
+        .unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn set_auto_renew_domain_error() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/domain/updateAutoRenew/example.com",
+        auth(json!({"status": "off"})),
+        json!({
+            "status": "SUCCESS",
+            "results": {"example.com": {"status": "ERROR", "message": "Domain is not in your account."}},
+        }),
+    );
+
+    let err = client(&server)
+        .set_auto_renew(&name("example.com"), false)
+        .unwrap_err();
+
+    mock.assert();
+    assert!(
+        matches!(&err, ApiError::Api { message, .. } if message == "Domain is not in your account."),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn error_status_on_http_200() {
+    let mut server = Server::new();
+    let mock = mock(
+        &mut server,
+        "/api/json/v3/dns/create/example.com",
+        Matcher::Any,
+        json!({"status": "ERROR", "message": "Invalid type."}),
+    );
+
+    let err = client(&server)
+        .create_dns(&name("example.com"), &Content::txt("hello"), None, None)
+        .unwrap_err();
+
+    mock.assert();
+    assert!(
+        matches!(&err, ApiError::Api { message, .. } if message == "Invalid type."),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn error_status_on_http_400() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_status(400)
+        .with_body(json!({"status": "ERROR", "message": "Invalid API key."}).to_string())
+        .create();
+
+    let err = client(&server).test_auth().unwrap_err();
+
+    mock.assert();
+    assert!(
+        matches!(&err, ApiError::Api { message, .. } if message == "Invalid API key."),
+        "{err:?}"
+    );
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn build_and_verify() {
+    let mut server = Server::new();
+    let success = mock(
+        &mut server,
+        "/api/json/v3/ping",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}),
+    );
+    let endpoint = format!("{}/api/json/v3/", server.url());
+    let builder = || {
+        Client::builder()
+            .endpoint_str(&endpoint)
+            .secretapikey("sk1_test")
+    };
+
+    builder().apikey("pk1_test").build_and_verify().unwrap();
+    success.assert();
+
+    let failure = server
+        .mock("POST", "/api/json/v3/ping")
+        .match_body(Matcher::PartialJson(json!({"apikey": "pk1_wrong"})))
+        .with_status(400)
+        .with_body(json!({"status": "ERROR", "message": "Invalid API key."}).to_string())
+        .create();
+
+    let err = builder()
+        .apikey("pk1_wrong")
+        .build_and_verify()
+        .unwrap_err();
+
+    failure.assert();
+    assert!(
+        matches!(&err, ClientBuilderError::Auth(ApiError::Api { message, .. }) if message == "Invalid API key."),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn retries_transient_auth_failure() {
+    let mut server = Server::new();
+    let failure = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_status(403)
+        .with_body(
+            json!({"status": "ERROR", "message": "Authentication failed, please try again."})
+                .to_string(),
+        )
+        .expect(1)
+        .create();
+    let success = mock(
+        &mut server,
+        "/api/json/v3/ping",
+        auth(json!({})),
+        json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}),
+    );
+
+    client(&server).test_auth().unwrap();
+
+    failure.assert();
+    success.assert();
+}
+
+#[test]
+fn not_opted_in_is_not_retried() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/dns/retrieve/example.com/")
+        .with_status(400)
+        .with_body(
+            json!({"status": "ERROR", "message": "Domain is not opted in to API access."})
+                .to_string(),
+        )
+        .expect(1)
+        .create();
+
+    let err = client(&server)
+        .retrieve_dns(&name("example.com"), None)
+        .unwrap_err();
+
+    mock.assert();
+    assert!(!err.is_transient(), "{err:?}");
+}
+
+#[test]
+fn retry_budget_exhausted() {
+    let mut server = Server::new();
+    let failure = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_status(403)
+        .with_body(
+            json!({"status": "ERROR", "message": "Authentication failed, please try again."})
+                .to_string(),
+        )
+        .expect(3)
+        .create();
+
+    let client = Client::builder()
+        .endpoint_str(&format!("{}/api/json/v3/", server.url()))
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .retry_budget(1)
+        .build()
+        .unwrap();
+
+    // The first call retries once, using up the budget; the second fails straight away.
+    assert!(client.test_auth().is_err());
+    assert!(client.test_auth().is_err());
+
+    failure.assert();
+}
+
+#[test]
+fn throttled_with_error_body_is_transient() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_status(429)
+        .with_body(
+            json!({"status": "ERROR", "message": "Request throttled. Please slow down."})
+                .to_string(),
+        )
+        .create();
+
+    let err = client(&server).test_auth().unwrap_err();
+
+    mock.assert();
+    assert!(matches!(err, ApiError::Api { .. }), "{err:?}");
+    assert!(err.is_transient());
+}
+
+#[test]
+fn last_response_meta() {
+    let mut server = Server::new();
+    let success = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_header("x-ratelimit-limit", "60")
+        .with_header("x-ratelimit-remaining", "59")
+        .with_body(json!({"status": "SUCCESS", "yourIp": "203.0.113.7"}).to_string())
+        .create();
+
+    let client = client(&server);
+    assert_eq!(client.last_response_meta(), None);
+    client.test_auth().unwrap();
+
+    success.assert();
+    let meta = client.last_response_meta().unwrap();
+    assert_eq!(meta.status, reqwest::StatusCode::OK);
+    assert_eq!(meta.retry_after, None);
+    let mut rate_limit = meta.rate_limit;
+    rate_limit.sort();
+    assert_eq!(
+        rate_limit,
+        [
+            ("x-ratelimit-limit".to_string(), "60".to_string()),
+            ("x-ratelimit-remaining".to_string(), "59".to_string()),
+        ]
+    );
+
+    success.remove();
+    let throttled = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_status(429)
+        .with_header("retry-after", "30")
+        .with_header("x-ratelimit-remaining", "0")
+        .with_body(json!({"status": "ERROR", "message": "Rate limit exceeded."}).to_string())
+        .create();
+
+    client.test_auth().unwrap_err();
+
+    throttled.assert();
+    let meta = client.last_response_meta().unwrap();
+    assert_eq!(meta.status, reqwest::StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(meta.retry_after, Some(Duration::from_secs(30)));
+    assert_eq!(
+        meta.rate_limit,
+        [("x-ratelimit-remaining".to_string(), "0".to_string())]
+    );
+}
+
+#[test]
+fn server_error_with_error_body_is_transient() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_status(503)
+        .with_body(
+            json!({"status": "ERROR", "message": "Service temporarily unavailable"}).to_string(),
+        )
+        .create();
+
+    let err = client(&server).test_auth().unwrap_err();
+
+    mock.assert();
+    assert!(
+        matches!(
+            &err,
+            ApiError::Api { status: Some(status), message }
+                if *status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+                    && message == "Service temporarily unavailable"
+        ),
+        "{err:?}"
+    );
+    assert!(err.is_transient());
+}
+
+#[test]
+fn http_error_without_body() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/json/v3/ping")
+        .with_status(503)
+        .create();
+
+    let err = client(&server).test_auth().unwrap_err();
+
+    mock.assert();
+    assert!(matches!(err, ApiError::Reqwest(_)), "{err:?}");
+    assert!(err.is_transient());
+}
@@ -0,0 +1,106 @@
+use addr::{parse_dns_name, parse_domain_name};
+use hamsando::{is_apex, split_domain, Client, DomainError};
+
+#[test]
+fn split_domain_com() {
+    let name = parse_domain_name("www.example.com").unwrap();
+    assert_eq!(
+        split_domain(&name).unwrap(),
+        (Some("www".into()), "example.com".into())
+    );
+}
+
+#[test]
+fn split_domain_co_uk() {
+    let name = parse_domain_name("www.example.co.uk").unwrap();
+    assert_eq!(
+        split_domain(&name).unwrap(),
+        (Some("www".into()), "example.co.uk".into())
+    );
+}
+
+#[test]
+fn split_domain_com_au() {
+    let name = parse_domain_name("a.b.example.com.au").unwrap();
+    assert_eq!(
+        split_domain(&name).unwrap(),
+        (Some("a.b".into()), "example.com.au".into())
+    );
+}
+
+#[test]
+fn split_domain_apex() {
+    let name = parse_domain_name("example.co.uk").unwrap();
+    assert_eq!(split_domain(&name).unwrap(), (None, "example.co.uk".into()));
+}
+
+#[test]
+fn is_apex_names() {
+    assert!(is_apex(&parse_domain_name("example.com").unwrap()));
+    assert!(is_apex(&parse_domain_name("example.co.uk").unwrap()));
+    assert!(!is_apex(&parse_domain_name("www.example.com").unwrap()));
+    assert!(!is_apex(&parse_domain_name("co.uk").unwrap()));
+}
+
+#[test]
+fn split_domain_idn() {
+    let name = parse_domain_name("www.bücher.café.com").unwrap();
+    let (prefix, root) = split_domain(&name).unwrap();
+    assert_eq!(prefix.as_deref(), Some("www.xn--bcher-kva"));
+    assert_eq!(root, "xn--caf-dma.com");
+}
+
+#[test]
+fn split_domain_public_suffix() {
+    for suffix in ["co.uk", "com"] {
+        let name = parse_domain_name(suffix).unwrap();
+        assert!(matches!(
+            split_domain(&name),
+            Err(DomainError::PublicSuffixOnly(s)) if s == suffix
+        ));
+    }
+}
+
+#[test]
+fn split_domain_trailing_dot() {
+    let name = parse_domain_name("host.example.com.").unwrap();
+    assert_eq!(
+        split_domain(&name).unwrap(),
+        (Some("host".into()), "example.com".into())
+    );
+}
+
+#[test]
+fn apex_of() {
+    let client = Client::builder()
+        .apikey("pk1_test")
+        .secretapikey("sk1_test")
+        .build()
+        .unwrap();
+
+    let name = parse_domain_name("a.b.example.co.uk").unwrap();
+    let apex = client.apex_of(&name).unwrap();
+    assert_eq!(apex.as_str(), "example.co.uk");
+    assert!(is_apex(&apex));
+
+    let name = parse_domain_name("co.uk").unwrap();
+    assert!(matches!(
+        client.apex_of(&name),
+        Err(DomainError::PublicSuffixOnly(_))
+    ));
+}
+
+#[test]
+fn split_domain_wildcard() {
+    let name = parse_dns_name("*.www.example.co.uk").unwrap();
+    assert_eq!(
+        split_domain(&name).unwrap(),
+        (Some("*.www".into()), "example.co.uk".into())
+    );
+
+    let name = parse_dns_name("*.co.uk").unwrap();
+    assert!(matches!(
+        split_domain(&name),
+        Err(DomainError::MissingRoot(_))
+    ));
+}
@@ -0,0 +1,15 @@
+use hamsando::Credentials;
+
+// Changing the environment races with anything reading it on another thread, like reqwest
+// looking up proxy variables, so this is the only test in its binary.
+#[test]
+fn credentials_from_env() {
+    std::env::set_var("PORKBUN_API_KEY", "pk1_env");
+    std::env::remove_var("PORKBUN_SECRET_API_KEY");
+    assert!(Credentials::from_env().is_err());
+
+    std::env::set_var("PORKBUN_SECRET_API_KEY", "sk1_env");
+    let credentials = Credentials::from_env().unwrap();
+    assert_eq!(credentials.apikey, "pk1_env");
+    assert_eq!(credentials.secretapikey, "sk1_env");
+}
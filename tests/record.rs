@@ -0,0 +1,654 @@
+use std::net::Ipv4Addr;
+
+use hamsando::record::{
+    cname_conflicts, diff, quote_txt, to_zonefile, unquote_txt, Content, Record, RecordDiff, Type,
+};
+use serde_json::json;
+
+#[test]
+fn record_serialize_round_trip() {
+    let record: Record = serde_json::from_value(json!({
+        "id": "106926652",
+        "name": "example.com",
+        "type": "A",
+        "content": "192.0.2.1",
+        "ttl": "600",
+        "prio": "0",
+        "notes": "",
+    }))
+    .unwrap();
+
+    let value = serde_json::to_value(&record).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "id": 106926652,
+            "name": "example.com",
+            "type": "A",
+            "content": "192.0.2.1",
+            "ttl": 600,
+            "prio": 0,
+            "notes": "",
+        })
+    );
+
+    let record: Record = serde_json::from_value(value).unwrap();
+    assert_eq!(record.id, 106926652);
+    assert_eq!(record.content, Content::A(Ipv4Addr::new(192, 0, 2, 1)));
+}
+
+#[test]
+fn svcb_round_trip() {
+    let content =
+        Content::from(&Type::Https, "1 . alpn=\"h2,h3\" no-default-alpn port=443").unwrap();
+
+    let Content::Https(svcb) = &content else {
+        panic!("expected HTTPS content, got {content:?}");
+    };
+    assert_eq!(svcb.priority, 1);
+    assert_eq!(svcb.target, ".");
+    assert_eq!(
+        svcb.params,
+        [
+            ("alpn".to_string(), "h2,h3".to_string()),
+            ("no-default-alpn".to_string(), String::new()),
+            ("port".to_string(), "443".to_string()),
+        ]
+    );
+    assert_eq!(
+        content.value_to_string(),
+        "1 . alpn=h2,h3 no-default-alpn port=443"
+    );
+}
+
+#[test]
+fn svcb_quoted_param_with_space() {
+    let content = Content::from(&Type::Https, "1 . alpn=\"h2, h3\" ech=\"AB CD\"").unwrap();
+
+    let Content::Https(svcb) = &content else {
+        panic!("expected HTTPS content, got {content:?}");
+    };
+    assert_eq!(
+        svcb.params,
+        [
+            ("alpn".to_string(), "h2, h3".to_string()),
+            ("ech".to_string(), "AB CD".to_string()),
+        ]
+    );
+    assert_eq!(
+        content.value_to_string(),
+        "1 . alpn=\"h2, h3\" ech=\"AB CD\""
+    );
+    assert_eq!(
+        Content::from(&Type::Https, &content.value_to_string()).unwrap(),
+        content
+    );
+}
+
+#[test]
+fn svcb_invalid() {
+    assert!(Content::from(&Type::Svcb, "70000 .").is_err());
+    assert!(Content::from(&Type::Svcb, "1").is_err());
+    assert!(Content::from(&Type::Svcb, "1 . =h2").is_err());
+    assert!(Content::from(&Type::Svcb, "1 . alpn=\"h2").is_err());
+}
+
+#[test]
+fn deserialize_a_record() {
+    let record: Record = serde_json::from_value(json!({
+        "id": "123456789",
+        "name": "www.example.com",
+        "type": "A",
+        "content": "192.0.2.1",
+        "ttl": "600",
+        "prio": null,
+        "notes": null,
+    }))
+    .unwrap();
+
+    assert_eq!(record.id, 123456789);
+    assert_eq!(record.name, "www.example.com");
+    assert_eq!(record.content, Content::A(Ipv4Addr::new(192, 0, 2, 1)));
+    assert_eq!(record.ttl, 600);
+    assert_eq!(record.prio, None);
+    assert_eq!(record.notes, None);
+}
+
+#[test]
+fn deserialize_numeric_fields() {
+    let record: Record = serde_json::from_value(json!({
+        "id": 123456789,
+        "name": "example.com",
+        "type": "AAAA",
+        "content": "2001:db8::1",
+        "ttl": 3600,
+        "prio": 0,
+        "notes": "",
+    }))
+    .unwrap();
+
+    assert_eq!(record.id, 123456789);
+    assert_eq!(
+        record.content,
+        Content::Aaaa("2001:db8::1".parse().unwrap())
+    );
+    assert_eq!(record.ttl, 3600);
+    assert_eq!(record.prio, Some(0));
+    assert_eq!(record.notes.as_deref(), Some(""));
+}
+
+#[test]
+fn deserialize_ignores_unknown_fields() {
+    let record: Record = serde_json::from_value(json!({
+        "id": "1",
+        "name": "example.com",
+        "type": "A",
+        "content": "192.0.2.1",
+        "ttl": "600",
+        "prio": null,
+        "notes": null,
+        "createdAt": "2026-01-01 00:00:00",
+    }))
+    .unwrap();
+
+    assert_eq!(record.content, Content::A(Ipv4Addr::new(192, 0, 2, 1)));
+    assert_eq!(record.ttl, 600);
+}
+
+#[test]
+fn deserialize_txt_with_quotes() {
+    let record: Record = serde_json::from_value(json!({
+        "id": "1",
+        "name": "example.com",
+        "type": "TXT",
+        "content": "v=spf1 include:_spf.example.com \"quoted\" ~all",
+        "ttl": "600",
+        "prio": null,
+        "notes": null,
+    }))
+    .unwrap();
+
+    assert_eq!(
+        record.content,
+        Content::Txt("v=spf1 include:_spf.example.com \"quoted\" ~all".to_string())
+    );
+}
+
+#[test]
+fn deserialize_mx_with_priority() {
+    let record: Record = serde_json::from_value(json!({
+        "id": "2",
+        "name": "example.com",
+        "type": "MX",
+        "content": "mail.example.com",
+        "ttl": "600",
+        "prio": "10",
+        "notes": "primary",
+    }))
+    .unwrap();
+
+    assert_eq!(record.content, Content::Mx("mail.example.com".to_string()));
+    assert_eq!(record.prio, Some(10));
+    assert_eq!(record.notes.as_deref(), Some("primary"));
+}
+
+#[test]
+fn deserialize_invalid_content() {
+    let err = serde_json::from_value::<Record>(json!({
+        "id": "3",
+        "name": "example.com",
+        "type": "A",
+        "content": "not-an-ip",
+        "ttl": "600",
+        "prio": null,
+        "notes": null,
+    }))
+    .unwrap_err();
+
+    assert!(
+        err.to_string().contains("invalid IPv4 address syntax"),
+        "{err}"
+    );
+}
+
+#[test]
+fn deserialize_invalid_id() {
+    assert!(serde_json::from_value::<Record>(json!({
+        "id": "abc",
+        "name": "example.com",
+        "type": "A",
+        "content": "192.0.2.1",
+        "ttl": "600",
+        "prio": null,
+        "notes": null,
+    }))
+    .is_err());
+}
+
+#[test]
+fn tlsa_round_trip() {
+    let content = Content::from(&Type::Tlsa, "3 1 1 0123456789abcdef").unwrap();
+
+    let Content::Tlsa(tlsa) = &content else {
+        panic!("expected TLSA content, got {content:?}");
+    };
+    assert_eq!(tlsa.usage, 3);
+    assert_eq!(tlsa.selector, 1);
+    assert_eq!(tlsa.matching_type, 1);
+    assert_eq!(tlsa.data, "0123456789abcdef");
+    assert_eq!(content.value_to_string(), "3 1 1 0123456789abcdef");
+}
+
+#[test]
+fn tlsa_invalid() {
+    assert!(Content::from(&Type::Tlsa, "4 1 1 abcd").is_err());
+    assert!(Content::from(&Type::Tlsa, "3 2 1 abcd").is_err());
+    assert!(Content::from(&Type::Tlsa, "3 1 3 abcd").is_err());
+    assert!(Content::from(&Type::Tlsa, "3 1 1 xyz").is_err());
+    assert!(Content::from(&Type::Tlsa, "3 1 1").is_err());
+}
+
+#[test]
+fn tlsa_split_data() {
+    let content = Content::from(&Type::Tlsa, "3 1 1 ABCD EF01").unwrap();
+    assert_eq!(content.value_to_string(), "3 1 1 ABCDEF01");
+}
+
+#[test]
+fn deserialize_unusual_tlsa() {
+    // Records Porkbun already stores are read even if they wouldn't be accepted when creating one.
+    let records: Vec<Record> = serde_json::from_value(json!([
+        {
+            "id": "1",
+            "name": "_443._tcp.example.com",
+            "type": "TLSA",
+            "content": "3 1 1 ABCD EF01",
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        },
+        {
+            "id": "2",
+            "name": "_25._tcp.example.com",
+            "type": "TLSA",
+            "content": "4 1 1 abcd",
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        },
+    ]))
+    .unwrap();
+
+    assert_eq!(records[0].content.value_to_string(), "3 1 1 ABCDEF01");
+    assert_eq!(records[1].content.value_to_string(), "4 1 1 abcd");
+}
+
+#[test]
+fn deserialize_unusual_svcb() {
+    let record: Record = serde_json::from_value(json!({
+        "id": "1",
+        "name": "example.com",
+        "type": "HTTPS",
+        "content": "1 . alpn=\"h2",
+        "ttl": "600",
+        "prio": null,
+        "notes": null,
+    }))
+    .unwrap();
+
+    let Content::Https(svcb) = &record.content else {
+        panic!("expected HTTPS content, got {:?}", record.content);
+    };
+    assert_eq!(svcb.params, [("alpn".to_string(), "h2".to_string())]);
+}
+
+#[test]
+fn txt_long_value_round_trip() {
+    let dkim = format!(
+        "v=DKIM1; k=rsa; p={}",
+        "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A".repeat(12)
+    );
+    assert!(dkim.len() > 255);
+
+    let content = Content::Txt(dkim.clone());
+    let quoted = content.value_to_string();
+
+    assert!(quoted.starts_with('"') && quoted.ends_with('"'));
+    let chunks = quoted[1..quoted.len() - 1]
+        .split("\" \"")
+        .collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks.iter().all(|chunk| chunk.len() <= 255));
+    assert_eq!(Content::from(&Type::Txt, &quoted).unwrap(), content);
+    assert_eq!(unquote_txt(&quote_txt(&dkim)), dkim);
+}
+
+#[test]
+fn txt_escapes_quotes_in_chunks() {
+    let value = format!("{}\"quoted\\\"", "a".repeat(300));
+
+    assert_eq!(unquote_txt(&quote_txt(&value)), value);
+}
+
+#[test]
+fn txt_short_value_unchanged() {
+    assert_eq!(quote_txt("hello world"), "hello world");
+    assert_eq!(unquote_txt("hello world"), "hello world");
+    assert_eq!(unquote_txt("\"unterminated"), "\"unterminated");
+}
+
+#[test]
+fn content_constructors() {
+    assert_eq!(
+        Content::cname("target.example.com"),
+        Content::Cname("target.example.com".to_string())
+    );
+    assert_eq!(
+        Content::from_ip("192.0.2.1".parse().unwrap()),
+        Content::a(Ipv4Addr::new(192, 0, 2, 1))
+    );
+    assert_eq!(
+        Content::from_ip("2001:db8::1".parse().unwrap()),
+        Content::aaaa("2001:db8::1".parse().unwrap())
+    );
+}
+
+#[test]
+fn is_spf() {
+    assert!(Content::txt("v=spf1 include:_spf.example.com ~all").is_spf());
+    assert!(Content::txt("V=SPF1 -all").is_spf());
+    assert!(Content::txt("\"v=spf1 -all\"").is_spf());
+    assert_eq!(Content::txt("\"v=spf1 -all\""), Content::txt("v=spf1 -all"));
+    assert!(Content::txt("v=spf1").is_spf());
+    assert!(!Content::txt("v=spf10 -all").is_spf());
+    assert!(!Content::txt("google-site-verification=abc").is_spf());
+    assert!(!Content::mx("v=spf1").is_spf());
+}
+
+#[test]
+fn record_matches() {
+    let record: Record = serde_json::from_value(json!({
+        "id": "1",
+        "name": "example.com",
+        "type": "MX",
+        "content": "mail.example.com",
+        "ttl": "600",
+        "prio": "10",
+        "notes": null,
+    }))
+    .unwrap();
+    let content = Content::mx("mail.example.com");
+
+    assert!(record.matches(&content, None, None));
+    assert!(record.matches(&content, Some(600), Some(10)));
+    assert!(!record.matches(&content, Some(3600), None));
+    assert!(!record.matches(&content, None, Some(20)));
+    assert!(!record.matches(&Content::mx("mx.example.com"), None, None));
+    assert_eq!(record.record_type(), Type::Mx);
+    assert_eq!(record.into_content(), content);
+}
+
+#[test]
+fn record_fqdn() {
+    let record = |name: &str| -> Record {
+        serde_json::from_value(json!({
+            "id": "1",
+            "name": name,
+            "type": "A",
+            "content": "192.0.2.1",
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        }))
+        .unwrap()
+    };
+
+    assert_eq!(record("host.example.com").fqdn(), "host.example.com");
+    assert_eq!(
+        record("host.example.com.").fqdn(),
+        record("host.example.com").fqdn()
+    );
+}
+
+#[test]
+fn diff_records() {
+    let record = |id: i64, content: &str| Record {
+        id,
+        name: "example.com".to_string(),
+        content: Content::txt(content),
+        ttl: 600,
+        prio: None,
+        notes: None,
+    };
+    let old = [record(1, "kept"), record(2, "before"), record(3, "removed")];
+    let new = [record(4, "added"), record(2, "after"), record(1, "kept")];
+
+    assert_eq!(
+        diff(&old, &new),
+        [
+            RecordDiff::Added(record(4, "added")),
+            RecordDiff::Changed {
+                old: record(2, "before"),
+                new: record(2, "after"),
+            },
+            RecordDiff::Removed(record(3, "removed")),
+        ]
+    );
+}
+
+#[test]
+fn find_cname_conflicts() {
+    let record = |id: i64, name: &str, content: Content| Record {
+        id,
+        name: name.to_string(),
+        content,
+        ttl: 600,
+        prio: None,
+        notes: None,
+    };
+    let records = [
+        record(1, "www.example.com", Content::cname("example.com")),
+        record(2, "example.com", Content::a(Ipv4Addr::new(192, 0, 2, 1))),
+        record(3, "example.com", Content::txt("v=spf1 -all")),
+    ];
+    let ids = |name, type_| {
+        cname_conflicts(&records, name, &type_)
+            .map(|record| record.id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ids("WWW.example.com.", Type::A), [1]);
+    assert_eq!(ids("www.example.com", Type::Cname), Vec::<i64>::new());
+    assert_eq!(ids("example.com", Type::Cname), [2, 3]);
+    assert_eq!(ids("example.com", Type::Mx), Vec::<i64>::new());
+}
+
+#[test]
+fn zonefile() {
+    let record = |id: i64, name: &str, content: Content, ttl: i64, prio: Option<i64>| Record {
+        id,
+        name: name.to_string(),
+        content,
+        ttl,
+        prio,
+        notes: None,
+    };
+    let records = [
+        record(
+            1,
+            "www.example.com",
+            Content::cname("example.com"),
+            3600,
+            None,
+        ),
+        record(2, "example.com", Content::txt("v=spf1 -all"), 600, None),
+        record(
+            3,
+            "example.com",
+            Content::mx("mail.example.com"),
+            600,
+            Some(10),
+        ),
+        record(
+            4,
+            "_sip._tcp.example.com",
+            Content::srv("5 5060 sip.example.com"),
+            600,
+            Some(10),
+        ),
+        record(
+            5,
+            "example.com",
+            Content::from(&Type::Https, "1 svc.example.net alpn=h2").unwrap(),
+            600,
+            None,
+        ),
+        record(
+            6,
+            "www.example.com",
+            Content::from(&Type::Https, "1 . alpn=h2").unwrap(),
+            600,
+            None,
+        ),
+    ];
+
+    assert_eq!(
+        to_zonefile("example.com", &records),
+        "$ORIGIN example.com.\n\
+         _sip._tcp\t600\tIN\tSRV\t10 5 5060 sip.example.com.\n\
+         @\t600\tIN\tMX\t10 mail.example.com.\n\
+         @\t600\tIN\tTXT\t\"v=spf1 -all\"\n\
+         @\t600\tIN\tHTTPS\t1 svc.example.net. alpn=h2\n\
+         www\t3600\tIN\tCNAME\texample.com.\n\
+         www\t600\tIN\tHTTPS\t1 . alpn=h2\n"
+    );
+}
+
+#[test]
+fn all_types() {
+    let types = Type::all().map(|type_| type_.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        ["A", "MX", "CNAME", "ALIAS", "TXT", "NS", "AAAA", "SRV", "TLSA", "CAA", "HTTPS", "SVCB"]
+    );
+}
+
+#[test]
+fn record_ordering() {
+    let record = |id: i64, name: &str, content: Content| Record {
+        id,
+        name: name.to_string(),
+        content,
+        ttl: 600,
+        prio: None,
+        notes: None,
+    };
+    let mut records = [
+        record(
+            1,
+            "www.example.com",
+            Content::A(Ipv4Addr::new(192, 0, 2, 1)),
+        ),
+        record(2, "example.com", Content::txt("b")),
+        record(3, "example.com", Content::txt("a")),
+        record(4, "example.com", Content::mx("mail.example.com")),
+        record(5, "example.com", Content::A(Ipv4Addr::new(192, 0, 2, 2))),
+    ];
+    records.sort();
+
+    assert_eq!(
+        records.iter().map(|record| record.id).collect::<Vec<_>>(),
+        [5, 4, 3, 2, 1]
+    );
+}
+
+#[test]
+fn deserialize_content_variations() {
+    let records: Vec<Record> = serde_json::from_value(json!([
+        {
+            "id": "1",
+            "name": "example.com",
+            "type": "TXT",
+            "content": "\"v=DKIM1; k=rsa; \" \"p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC\"",
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        },
+        {
+            "id": "2",
+            "name": "example.com",
+            "type": "TXT",
+            "content": ["v=DKIM1; k=rsa; ", "p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC"],
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        },
+        {
+            "id": "3",
+            "name": "example.com",
+            "type": "CAA",
+            "content": "0 issue \"letsencrypt.org\"",
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        },
+        {
+            "id": "4",
+            "name": "example.com",
+            "type": "HTTPS",
+            "content": "1 . alpn=\"h2,h3\"",
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        },
+        {
+            "id": "5",
+            "name": "example.com",
+            "type": "A",
+            "content": " 192.0.2.1\n",
+            "ttl": "600",
+            "prio": null,
+            "notes": null,
+        },
+    ]))
+    .unwrap();
+
+    let dkim = Content::txt("v=DKIM1; k=rsa; p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC");
+    assert_eq!(records[0].content, dkim);
+    assert_eq!(records[1].content, dkim);
+    assert_eq!(
+        records[2].content,
+        Content::caa("0 issue \"letsencrypt.org\"")
+    );
+    assert_eq!(
+        records[3].content,
+        Content::Https("1 . alpn=\"h2,h3\"".parse().unwrap())
+    );
+    assert_eq!(records[4].content, Content::A(Ipv4Addr::new(192, 0, 2, 1)));
+}
+
+#[test]
+fn parse_type() {
+    assert_eq!("AAAA".parse::<Type>().unwrap(), Type::Aaaa);
+    assert_eq!("txt".parse::<Type>().unwrap(), Type::Txt);
+
+    let err = "PTR".parse::<Type>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unsupported record type \"PTR\", expected one of A, MX, CNAME, ALIAS, TXT, NS, AAAA, SRV, TLSA, CAA, HTTPS, SVCB"
+    );
+}
+
+#[test]
+fn hostnames_ignore_case() {
+    assert_eq!(
+        Content::cname("Target.Example.com"),
+        Content::cname("target.example.com")
+    );
+    assert_eq!(
+        Content::mx("MAIL.example.com"),
+        Content::mx("mail.example.com")
+    );
+    assert_ne!(Content::txt("Hello"), Content::txt("hello"));
+    assert_ne!(Content::cname("example.com"), Content::alias("example.com"));
+}
@@ -0,0 +1,35 @@
+use hamsando::ttl::{deserialize_ttl, parse_ttl, TtlError};
+use serde::Deserialize;
+
+#[test]
+fn parse_seconds_and_durations() {
+    assert_eq!(parse_ttl("3600"), Ok(3600));
+    assert_eq!(parse_ttl("1h"), Ok(3600));
+    assert_eq!(parse_ttl("1h30m"), Ok(5400));
+    assert_eq!(parse_ttl("10m"), Ok(600));
+    assert_eq!(parse_ttl("1d"), Ok(86400));
+    assert_eq!(parse_ttl("1w"), Ok(604800));
+}
+
+#[test]
+fn parse_rejects_invalid() {
+    for input in ["", "h", "1x", "1h30", "one hour"] {
+        assert_eq!(parse_ttl(input), Err(TtlError::Invalid(input.to_string())));
+    }
+    assert_eq!(parse_ttl("5m"), Err(TtlError::BelowMinimum(300)));
+}
+
+#[test]
+fn deserialize_number_or_string() {
+    #[derive(Deserialize)]
+    struct Config {
+        #[serde(deserialize_with = "deserialize_ttl")]
+        ttl: i64,
+    }
+
+    let config: Config = serde_json::from_str(r#"{"ttl": 3600}"#).unwrap();
+    assert_eq!(config.ttl, 3600);
+    let config: Config = serde_json::from_str(r#"{"ttl": "2h"}"#).unwrap();
+    assert_eq!(config.ttl, 7200);
+    assert!(serde_json::from_str::<Config>(r#"{"ttl": 60}"#).is_err());
+}